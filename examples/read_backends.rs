@@ -0,0 +1,62 @@
+//! Compares the read backends by hashing a file with XXH3, which is fast enough for the read to
+//! dominate.
+//!
+//! ```sh
+//! cargo run --release --example read_backends -- <FILE> [ITERATIONS]
+//! ```
+//!
+//! The buffered and mmap backends are served from the page cache after the first iteration unless
+//! the cache is dropped in between, e.g. `echo 1 > /proc/sys/vm/drop_caches` on Linux.
+
+use hash_gui::algorithm::Registry;
+use hash_gui::engine::{self, HashOutcome, ReadBackend};
+use hash_gui::prelude::*;
+use std::ops::ControlFlow;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+fn main() -> Fallible<()> {
+    let mut args = std::env::args_os().skip(1);
+    let pathname = PathBuf::from(
+        args.next()
+            .context("usage: read_backends <FILE> [ITERATIONS]")?,
+    );
+    let iterations = match args.next() {
+        Some(data) => data
+            .to_str()
+            .context("iterations")?
+            .parse::<u32>()
+            .context("iterations")?,
+        None => 3,
+    };
+
+    let algorithms = [Registry::default()
+        .find("XXH3")
+        .context("XXH3 is registered by default")?];
+    let filesize = std::fs::metadata(&pathname)?.len();
+
+    for backend in ReadBackend::ALL {
+        let mut elapsed = Duration::MAX;
+        let mut digest = None;
+        for _ in 0..iterations {
+            let started = Instant::now();
+            let outcome = engine::hash_file(&pathname, &algorithms, backend, |_| {
+                ControlFlow::Continue(())
+            })?;
+            elapsed = elapsed.min(started.elapsed());
+
+            match outcome {
+                HashOutcome::Finished(results) => digest = Some(results[0].value.to_string()),
+                outcome => bail!("{backend}: {outcome:?}"),
+            }
+        }
+
+        println!(
+            "{backend:>10}: {:>8.1} MiB/s (best of {iterations}, {})",
+            filesize as f64 / 1024.0 / 1024.0 / elapsed.as_secs_f64(),
+            digest.unwrap_or_default(),
+        );
+    }
+
+    Ok(())
+}
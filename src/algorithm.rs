@@ -0,0 +1,871 @@
+mod checksum;
+mod mac;
+
+pub use checksum::{
+    Adler32, Checksum, ChecksumAlgorithm, Crc32, Crc32c, Crc64, Xxh3, Xxh64, Xxh128,
+};
+pub use mac::{HmacAlgorithm, KmacAlgorithm};
+
+use crate::engine::HashValue;
+use crate::prelude::*;
+use sha2::Digest;
+use sha2::digest::block_buffer::Lazy;
+use sha2::digest::core_api::{Block, Buffer, OutputSizeUser, VariableOutputCore};
+use sha2::digest::typenum::{IsLess, NonZero, U256};
+use sha2::digest::{ExtendableOutput, Output, Update};
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+pub trait Algorithm: Debug + Send + Sync {
+    /// Key in the [`Registry`].
+    fn name(&self) -> &str;
+
+    /// Label shown in the UI, including parameters such as the output size.
+    fn label(&self) -> String {
+        self.name().to_string()
+    }
+
+    /// Digest length in bytes.
+    fn output_size(&self) -> usize;
+
+    fn category(&self) -> Category {
+        Category::Cryptographic
+    }
+
+    /// Allowed output sizes in bytes for algorithms with a variable-length output.
+    fn output_size_range(&self) -> Option<RangeInclusive<usize>> {
+        None
+    }
+
+    /// Maximum lengths of the optional parameters. `0` means not supported.
+    fn param_limits(&self) -> ParamLimits {
+        ParamLimits::default()
+    }
+
+    /// Parameters this instance was configured with.
+    fn params(&self) -> Params {
+        Params::default()
+    }
+
+    /// Returns the same algorithm configured with `params`.
+    fn with_params(&self, _params: &Params) -> Fallible<Arc<dyn Algorithm>> {
+        bail!("{} has no parameters", self.name())
+    }
+
+    fn hasher(&self) -> Box<dyn Hasher>;
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Category {
+    Cryptographic,
+    /// Collisions are practical. A match does not prove the content was not tampered with.
+    Insecure,
+    /// Detects accidental corruption only.
+    Checksum,
+    /// Requires a secret key.
+    Mac,
+}
+
+/// Results are only comparable when computed by the same algorithm with identical parameters.
+pub fn is_comparable(algorithm: &dyn Algorithm, other: &dyn Algorithm) -> bool {
+    algorithm.name() == other.name() && algorithm.params() == other.params()
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ParamLimits {
+    /// `usize::MAX` means any length.
+    pub key: usize,
+    pub salt: usize,
+    pub personalization: usize,
+}
+
+impl ParamLimits {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Clone, Default, Eq, Hash, PartialEq)]
+pub struct Params {
+    /// `None` uses the default output size of the algorithm.
+    pub output_size: Option<usize>,
+    pub key: Vec<u8>,
+    pub salt: Vec<u8>,
+    pub personalization: Vec<u8>,
+}
+
+impl Params {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Debug for Params {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // never leaks the key into logs.
+        f.debug_struct("Params")
+            .field("output_size", &self.output_size)
+            .field("key", &format_args!("<{} bytes>", self.key.len()))
+            .field("salt", &self.salt)
+            .field("personalization", &self.personalization)
+            .finish()
+    }
+}
+
+pub trait Hasher: Send {
+    fn update(&mut self, data: &[u8]);
+
+    fn finalize(self: Box<Self>) -> HashValue;
+}
+
+pub struct DigestAlgorithm<D> {
+    name: &'static str,
+    category: Category,
+    _digest: PhantomData<fn() -> D>,
+}
+
+impl<D> DigestAlgorithm<D> {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            category: Category::Cryptographic,
+            _digest: PhantomData,
+        }
+    }
+
+    pub const fn insecure(self) -> Self {
+        Self {
+            category: Category::Insecure,
+            ..self
+        }
+    }
+}
+
+impl<D> Debug for DigestAlgorithm<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DigestAlgorithm")
+            .field("name", &self.name)
+            .field("category", &self.category)
+            .finish()
+    }
+}
+
+impl<D: Digest + Send + 'static> Algorithm for DigestAlgorithm<D> {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn output_size(&self) -> usize {
+        <D as Digest>::output_size()
+    }
+
+    fn category(&self) -> Category {
+        self.category
+    }
+
+    fn hasher(&self) -> Box<dyn Hasher> {
+        Box::new(DigestHasher(D::new()))
+    }
+}
+
+struct DigestHasher<D>(D);
+
+impl<D: Digest + Send> Hasher for DigestHasher<D> {
+    fn update(&mut self, data: &[u8]) {
+        Digest::update(&mut self.0, data);
+    }
+
+    fn finalize(self: Box<Self>) -> HashValue {
+        HashValue::new(self.0.finalize().to_vec())
+    }
+}
+
+pub struct XofAlgorithm<D> {
+    name: &'static str,
+    output_size: usize,
+    _xof: PhantomData<fn() -> D>,
+}
+
+impl<D> XofAlgorithm<D> {
+    const MAX_OUTPUT_SIZE: usize = 1024;
+
+    pub const fn new(name: &'static str, output_size: usize) -> Self {
+        Self {
+            name,
+            output_size,
+            _xof: PhantomData,
+        }
+    }
+}
+
+impl<D> Debug for XofAlgorithm<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("XofAlgorithm")
+            .field("name", &self.name)
+            .field("output_size", &self.output_size)
+            .finish()
+    }
+}
+
+impl<D: Default + Update + ExtendableOutput + Send + 'static> Algorithm for XofAlgorithm<D> {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn label(&self) -> String {
+        format!("{}/{}", self.name, self.output_size * 8)
+    }
+
+    fn output_size(&self) -> usize {
+        self.output_size
+    }
+
+    fn output_size_range(&self) -> Option<RangeInclusive<usize>> {
+        Some(1..=Self::MAX_OUTPUT_SIZE)
+    }
+
+    fn params(&self) -> Params {
+        Params {
+            output_size: Some(self.output_size),
+            ..Params::default()
+        }
+    }
+
+    fn with_params(&self, params: &Params) -> Fallible<Arc<dyn Algorithm>> {
+        ensure!(
+            params.key.is_empty() && params.salt.is_empty() && params.personalization.is_empty(),
+            "{} only accepts the output size",
+            self.name,
+        );
+
+        let output_size = params.output_size.unwrap_or(self.output_size);
+        ensure!(
+            (1..=Self::MAX_OUTPUT_SIZE).contains(&output_size),
+            "{} output size must be 1-{} bytes",
+            self.name,
+            Self::MAX_OUTPUT_SIZE,
+        );
+
+        Ok(Arc::new(Self::new(self.name, output_size)))
+    }
+
+    fn hasher(&self) -> Box<dyn Hasher> {
+        Box::new(XofHasher {
+            xof: D::default(),
+            output_size: self.output_size,
+        })
+    }
+}
+
+struct XofHasher<D> {
+    xof: D,
+    output_size: usize,
+}
+
+impl<D: Update + ExtendableOutput + Send> Hasher for XofHasher<D> {
+    fn update(&mut self, data: &[u8]) {
+        Update::update(&mut self.xof, data);
+    }
+
+    fn finalize(self: Box<Self>) -> HashValue {
+        let mut buf = vec![0; self.output_size];
+        self.xof.finalize_xof_into(&mut buf);
+        HashValue::new(buf)
+    }
+}
+
+pub trait Blake2Core:
+    VariableOutputCore<BufferKind = Lazy, BlockSize: IsLess<U256, Output: NonZero>>
+    + Clone
+    + Send
+    + 'static
+{
+    const NAME: &'static str;
+
+    fn new_with_params(salt: &[u8], persona: &[u8], key_size: usize, output_size: usize) -> Self;
+}
+
+impl Blake2Core for blake2::Blake2bVarCore {
+    const NAME: &'static str = "BLAKE2b";
+
+    fn new_with_params(salt: &[u8], persona: &[u8], key_size: usize, output_size: usize) -> Self {
+        Self::new_with_params(salt, persona, key_size, output_size)
+    }
+}
+
+impl Blake2Core for blake2::Blake2sVarCore {
+    const NAME: &'static str = "BLAKE2s";
+
+    fn new_with_params(salt: &[u8], persona: &[u8], key_size: usize, output_size: usize) -> Self {
+        Self::new_with_params(salt, persona, key_size, output_size)
+    }
+}
+
+/// BLAKE2 with the parameter block exposed, which the `blake2` crate only offers for MACs of a
+/// fixed output size.
+pub struct Blake2Algorithm<C> {
+    output_size: usize,
+    params: Params,
+    _core: PhantomData<fn() -> C>,
+}
+
+impl<C: Blake2Core> Blake2Algorithm<C> {
+    fn max_output_size() -> usize {
+        <C as OutputSizeUser>::output_size()
+    }
+
+    fn limits() -> ParamLimits {
+        ParamLimits {
+            key: Self::max_output_size(),
+            salt: Self::max_output_size() / 4,
+            personalization: Self::max_output_size() / 4,
+        }
+    }
+}
+
+impl<C: Blake2Core> Default for Blake2Algorithm<C> {
+    fn default() -> Self {
+        Self {
+            output_size: Self::max_output_size(),
+            params: Params::default(),
+            _core: PhantomData,
+        }
+    }
+}
+
+impl<C> Debug for Blake2Algorithm<C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Blake2Algorithm")
+            .field("output_size", &self.output_size)
+            .field("params", &self.params)
+            .finish()
+    }
+}
+
+impl<C: Blake2Core> Algorithm for Blake2Algorithm<C> {
+    fn name(&self) -> &str {
+        C::NAME
+    }
+
+    fn label(&self) -> String {
+        let mut label = format!("{}-{}", C::NAME, self.output_size * 8);
+        if !self.params.key.is_empty() {
+            label.push_str(" keyed");
+        }
+        if !self.params.salt.is_empty() {
+            label.push_str(&format!(
+                " salt={}",
+                HashValue::new(self.params.salt.clone())
+            ));
+        }
+        if !self.params.personalization.is_empty() {
+            label.push_str(&format!(
+                " personal={}",
+                HashValue::new(self.params.personalization.clone())
+            ));
+        }
+        label
+    }
+
+    fn output_size(&self) -> usize {
+        self.output_size
+    }
+
+    fn output_size_range(&self) -> Option<RangeInclusive<usize>> {
+        Some(1..=Self::max_output_size())
+    }
+
+    fn param_limits(&self) -> ParamLimits {
+        Self::limits()
+    }
+
+    fn params(&self) -> Params {
+        Params {
+            output_size: Some(self.output_size),
+            ..self.params.clone()
+        }
+    }
+
+    fn with_params(&self, params: &Params) -> Fallible<Arc<dyn Algorithm>> {
+        let output_size = params.output_size.unwrap_or(self.output_size);
+        ensure!(
+            (1..=Self::max_output_size()).contains(&output_size),
+            "{} output size must be 1-{} bytes",
+            C::NAME,
+            Self::max_output_size(),
+        );
+
+        let limits = Self::limits();
+        ensure!(
+            params.key.len() <= limits.key,
+            "{} key must be at most {} bytes",
+            C::NAME,
+            limits.key,
+        );
+        ensure!(
+            params.salt.len() <= limits.salt,
+            "{} salt must be at most {} bytes",
+            C::NAME,
+            limits.salt,
+        );
+        ensure!(
+            params.personalization.len() <= limits.personalization,
+            "{} personalization must be at most {} bytes",
+            C::NAME,
+            limits.personalization,
+        );
+
+        Ok(Arc::new(Self {
+            output_size,
+            params: Params {
+                output_size: None,
+                ..params.clone()
+            },
+            _core: PhantomData,
+        }))
+    }
+
+    fn hasher(&self) -> Box<dyn Hasher> {
+        let core = C::new_with_params(
+            &self.params.salt,
+            &self.params.personalization,
+            self.params.key.len(),
+            self.output_size,
+        );
+
+        // a keyed hash processes the zero-padded key as the first block.
+        let buffer = if self.params.key.is_empty() {
+            Buffer::<C>::default()
+        } else {
+            let mut block = Block::<C>::default();
+            block[..self.params.key.len()].copy_from_slice(&self.params.key);
+            Buffer::<C>::new(&block)
+        };
+
+        Box::new(Blake2Hasher {
+            core,
+            buffer,
+            output_size: self.output_size,
+        })
+    }
+}
+
+struct Blake2Hasher<C: Blake2Core> {
+    core: C,
+    buffer: Buffer<C>,
+    output_size: usize,
+}
+
+impl<C: Blake2Core> Hasher for Blake2Hasher<C> {
+    fn update(&mut self, data: &[u8]) {
+        let Self { core, buffer, .. } = self;
+        buffer.digest_blocks(data, |blocks| core.update_blocks(blocks));
+    }
+
+    fn finalize(mut self: Box<Self>) -> HashValue {
+        let mut buf = Output::<C>::default();
+        let Self { core, buffer, .. } = &mut *self;
+        core.finalize_variable_core(buffer, &mut buf);
+        HashValue::new(buf[..self.output_size].to_vec())
+    }
+}
+
+#[derive(Default)]
+pub struct Blake3Algorithm {
+    key: Option<[u8; blake3::KEY_LEN]>,
+}
+
+impl Blake3Algorithm {
+    /// `update_rayon` is slower than `update` below 128 KiB according to the blake3 docs.
+    const PARALLEL_THRESHOLD: usize = 128 * 1024;
+}
+
+impl Debug for Blake3Algorithm {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Blake3Algorithm")
+            .field("keyed", &self.key.is_some())
+            .finish()
+    }
+}
+
+impl Algorithm for Blake3Algorithm {
+    fn name(&self) -> &str {
+        "BLAKE3"
+    }
+
+    fn label(&self) -> String {
+        match self.key {
+            Some(_) => "BLAKE3 keyed".into(),
+            None => "BLAKE3".into(),
+        }
+    }
+
+    fn output_size(&self) -> usize {
+        blake3::OUT_LEN
+    }
+
+    fn param_limits(&self) -> ParamLimits {
+        ParamLimits {
+            key: blake3::KEY_LEN,
+            ..ParamLimits::default()
+        }
+    }
+
+    fn params(&self) -> Params {
+        Params {
+            key: self.key.map(|data| data.to_vec()).unwrap_or_default(),
+            ..Params::default()
+        }
+    }
+
+    fn with_params(&self, params: &Params) -> Fallible<Arc<dyn Algorithm>> {
+        ensure!(
+            params.output_size.is_none()
+                && params.salt.is_empty()
+                && params.personalization.is_empty(),
+            "BLAKE3 only accepts a key",
+        );
+
+        let key = match params.key.len() {
+            0 => None,
+            blake3::KEY_LEN => Some(params.key.as_slice().try_into()?),
+            _ => bail!("BLAKE3 key must be {} bytes", blake3::KEY_LEN),
+        };
+        Ok(Arc::new(Self { key }))
+    }
+
+    fn hasher(&self) -> Box<dyn Hasher> {
+        Box::new(Blake3Hasher(match &self.key {
+            Some(key) => blake3::Hasher::new_keyed(key),
+            None => blake3::Hasher::new(),
+        }))
+    }
+}
+
+struct Blake3Hasher(blake3::Hasher);
+
+impl Hasher for Blake3Hasher {
+    fn update(&mut self, data: &[u8]) {
+        if Blake3Algorithm::PARALLEL_THRESHOLD <= data.len() {
+            self.0.update_rayon(data);
+        } else {
+            self.0.update(data);
+        }
+    }
+
+    fn finalize(self: Box<Self>) -> HashValue {
+        HashValue::new(self.0.finalize().as_bytes().to_vec())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Registry {
+    algorithms: Vec<Arc<dyn Algorithm>>,
+}
+
+impl Registry {
+    pub fn empty() -> Self {
+        Self { algorithms: vec![] }
+    }
+
+    /// Replaces the algorithm with the same name if any.
+    pub fn register(&mut self, algorithm: Arc<dyn Algorithm>) {
+        match self
+            .algorithms
+            .iter_mut()
+            .find(|data| data.name() == algorithm.name())
+        {
+            Some(data) => *data = algorithm,
+            None => self.algorithms.push(algorithm),
+        }
+    }
+
+    /// Returns the algorithm configured with `params`, or the registered one as is when the
+    /// params are the default.
+    pub fn configure(&self, name: &str, params: &Params) -> Fallible<Arc<dyn Algorithm>> {
+        let algorithm = self
+            .find(name)
+            .with_context(|| format!("unknown algorithm: {name}"))?;
+        ensure!(
+            algorithm.category() != Category::Mac || !params.key.is_empty(),
+            "{} requires a key",
+            algorithm.name(),
+        );
+        if params.is_default() {
+            Ok(algorithm)
+        } else {
+            algorithm.with_params(params)
+        }
+    }
+
+    pub fn find(&self, name: &str) -> Option<Arc<dyn Algorithm>> {
+        self.algorithms
+            .iter()
+            .find(|data| data.name().eq_ignore_ascii_case(name))
+            .cloned()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<dyn Algorithm>> {
+        self.algorithms.iter()
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register(Arc::new(DigestAlgorithm::<sha2::Sha224>::new("SHA224")));
+        registry.register(Arc::new(DigestAlgorithm::<sha2::Sha256>::new("SHA256")));
+        registry.register(Arc::new(DigestAlgorithm::<sha2::Sha384>::new("SHA384")));
+        registry.register(Arc::new(DigestAlgorithm::<sha2::Sha512>::new("SHA512")));
+        registry.register(Arc::new(DigestAlgorithm::<sha2::Sha512_224>::new(
+            "SHA512/224",
+        )));
+        registry.register(Arc::new(DigestAlgorithm::<sha2::Sha512_256>::new(
+            "SHA512/256",
+        )));
+        registry.register(Arc::new(DigestAlgorithm::<sha3::Sha3_224>::new("SHA3-224")));
+        registry.register(Arc::new(DigestAlgorithm::<sha3::Sha3_256>::new("SHA3-256")));
+        registry.register(Arc::new(DigestAlgorithm::<sha3::Sha3_384>::new("SHA3-384")));
+        registry.register(Arc::new(DigestAlgorithm::<sha3::Sha3_512>::new("SHA3-512")));
+        registry.register(Arc::new(DigestAlgorithm::<sha3::Keccak256>::new(
+            "Keccak256",
+        )));
+        registry.register(Arc::new(XofAlgorithm::<sha3::Shake128>::new(
+            "SHAKE128", 32,
+        )));
+        registry.register(Arc::new(XofAlgorithm::<sha3::Shake256>::new(
+            "SHAKE256", 64,
+        )));
+        registry.register(Arc::new(
+            Blake2Algorithm::<blake2::Blake2bVarCore>::default(),
+        ));
+        registry.register(Arc::new(
+            Blake2Algorithm::<blake2::Blake2sVarCore>::default(),
+        ));
+        registry.register(Arc::new(Blake3Algorithm::default()));
+        registry.register(Arc::new(DigestAlgorithm::<streebog::Streebog256>::new(
+            "Streebog-256",
+        )));
+        registry.register(Arc::new(DigestAlgorithm::<streebog::Streebog512>::new(
+            "Streebog-512",
+        )));
+        registry.register(Arc::new(DigestAlgorithm::<sm3::Sm3>::new("SM3")));
+        registry.register(Arc::new(DigestAlgorithm::<md5::Md5>::new("MD5").insecure()));
+        registry.register(Arc::new(
+            DigestAlgorithm::<sha1::Sha1>::new("SHA1").insecure(),
+        ));
+        registry.register(Arc::new(
+            DigestAlgorithm::<ripemd::Ripemd160>::new("RIPEMD160").insecure(),
+        ));
+        registry.register(Arc::new(ChecksumAlgorithm::<Crc32>::new("CRC32")));
+        registry.register(Arc::new(ChecksumAlgorithm::<Crc32c>::new("CRC32C")));
+        registry.register(Arc::new(ChecksumAlgorithm::<Crc64>::new("CRC64")));
+        registry.register(Arc::new(ChecksumAlgorithm::<Adler32>::new("Adler32")));
+        registry.register(Arc::new(ChecksumAlgorithm::<Xxh64>::new("XXH64")));
+        registry.register(Arc::new(ChecksumAlgorithm::<Xxh3>::new("XXH3")));
+        registry.register(Arc::new(ChecksumAlgorithm::<Xxh128>::new("XXH128")));
+        registry.register(Arc::new(HmacAlgorithm::<sha2::Sha256>::new("HMAC-SHA256")));
+        registry.register(Arc::new(HmacAlgorithm::<sha2::Sha512>::new("HMAC-SHA512")));
+        registry.register(Arc::new(KmacAlgorithm::new128()));
+        registry.register(Arc::new(KmacAlgorithm::new256()));
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blake2::digest::{Mac, VariableOutput};
+
+    fn digest(algorithm: &dyn Algorithm, params: Params, chunks: &[&[u8]]) -> Vec<u8> {
+        let mut hasher = algorithm.with_params(&params).unwrap().hasher();
+        for data in chunks {
+            hasher.update(data);
+        }
+        hasher.finalize().as_bytes().to_vec()
+    }
+
+    fn key(len: usize) -> Vec<u8> {
+        (0..len as u8).collect()
+    }
+
+    /// BLAKE2 reference keyed KAT with the key `00..3f` and no input.
+    #[test]
+    fn blake2b_keyed_empty() {
+        let params = Params {
+            key: key(64),
+            ..Default::default()
+        };
+        let actual = digest(
+            &Blake2Algorithm::<blake2::Blake2bVarCore>::default(),
+            params,
+            &[],
+        );
+        assert_eq!(
+            HashValue::new(actual.clone()).to_string(),
+            "10ebb67700b1868efb4417987acf4690ae9d972fb7a590c2f02871799aaa4786\
+             b5e996e8f0f4eb981fc214b005f42d2ff4233499391653df7aefcbc13fc51568",
+        );
+
+        let expected = blake2::Blake2bMac512::new_from_slice(&key(64))
+            .unwrap()
+            .finalize()
+            .into_bytes();
+        assert_eq!(actual, expected.as_slice());
+    }
+
+    /// The key block must not be mistaken for the last block when the input fills exactly one.
+    #[test]
+    fn blake2_keyed_one_block() {
+        let data = vec![0xa5; 128];
+        let params = Params {
+            key: key(32),
+            ..Default::default()
+        };
+        let mut mac = blake2::Blake2bMac512::new_from_slice(&key(32)).unwrap();
+        Mac::update(&mut mac, &data);
+        assert_eq!(
+            digest(
+                &Blake2Algorithm::<blake2::Blake2bVarCore>::default(),
+                params.clone(),
+                &[&data[..100], &data[100..]],
+            ),
+            mac.finalize().into_bytes().as_slice(),
+        );
+
+        let mut mac = blake2::Blake2sMac256::new_from_slice(&key(32)).unwrap();
+        Mac::update(&mut mac, &data[..64]);
+        assert_eq!(
+            digest(
+                &Blake2Algorithm::<blake2::Blake2sVarCore>::default(),
+                params,
+                &[&data[..64]],
+            ),
+            mac.finalize().into_bytes().as_slice(),
+        );
+    }
+
+    #[test]
+    fn blake2_salt_personalization() {
+        let params = Params {
+            key: key(16),
+            salt: b"saltsaltsaltsalt".to_vec(),
+            personalization: b"personalpersonal".to_vec(),
+            ..Default::default()
+        };
+        let mut mac = blake2::Blake2bMac512::new_with_salt_and_personal(
+            &params.key,
+            &params.salt,
+            &params.personalization,
+        )
+        .unwrap();
+        Mac::update(&mut mac, b"abc");
+        assert_eq!(
+            digest(
+                &Blake2Algorithm::<blake2::Blake2bVarCore>::default(),
+                params,
+                &[b"abc"],
+            ),
+            mac.finalize().into_bytes().as_slice(),
+        );
+
+        let params = Params {
+            key: key(16),
+            salt: b"saltsalt".to_vec(),
+            personalization: b"personal".to_vec(),
+            ..Default::default()
+        };
+        let mut mac = blake2::Blake2sMac256::new_with_salt_and_personal(
+            &params.key,
+            &params.salt,
+            &params.personalization,
+        )
+        .unwrap();
+        Mac::update(&mut mac, b"abc");
+        assert_eq!(
+            digest(
+                &Blake2Algorithm::<blake2::Blake2sVarCore>::default(),
+                params,
+                &[b"abc"],
+            ),
+            mac.finalize().into_bytes().as_slice(),
+        );
+    }
+
+    #[test]
+    fn blake2b_output_size_32() {
+        let params = Params {
+            output_size: Some(32),
+            ..Default::default()
+        };
+        let actual = digest(
+            &Blake2Algorithm::<blake2::Blake2bVarCore>::default(),
+            params,
+            &[b"abc"],
+        );
+        assert_eq!(
+            HashValue::new(actual.clone()).to_string(),
+            "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319",
+        );
+
+        let mut expected = [0; 32];
+        let mut hasher = blake2::Blake2bVar::new(32).unwrap();
+        Update::update(&mut hasher, b"abc");
+        hasher.finalize_variable(&mut expected).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    fn hash(name: &str, chunks: &[&[u8]]) -> String {
+        let mut hasher = Registry::default().find(name).unwrap().hasher();
+        for data in chunks {
+            hasher.update(data);
+        }
+        hasher.finalize().to_string()
+    }
+
+    /// RFC 6986 10.1.
+    const STREEBOG_M1: &[u8] = b"012345678901234567890123456789012345678901234567890123456789012";
+
+    /// RFC 6986 10.2, stored in the byte order hashed.
+    const STREEBOG_M2: &str = "d1e520e2e5f2f0e82c20d1f2f0e8e1eee6e820e2edf3f6e82c20e2e5fef2fa20f1\
+                               20eceef0ff20f1f2f0e5ebe0ece820ede020f5f0e0e1f0fbff20efebfaeafb20c8e3\
+                               eef0e5e2fb";
+
+    #[test]
+    fn streebog_rfc_6986() {
+        let m2 = hex::decode(STREEBOG_M2).unwrap();
+        assert_eq!(
+            hash("Streebog-256", &[STREEBOG_M1]),
+            "9d151eefd8590b89daa6ba6cb74af9275dd051026bb149a452fd84e5e57b5500",
+        );
+        assert_eq!(
+            hash("Streebog-512", &[STREEBOG_M1]),
+            "1b54d01a4af5b9d5cc3d86d68d285462b19abc2475222f35c085122be4ba1ffa\
+             00ad30f8767b3a82384c6574f024c311e2a481332b08ef7f41797891c1646f48",
+        );
+        assert_eq!(
+            hash("Streebog-256", &[&m2[..60], &m2[60..]]),
+            "9dd2fe4e90409e5da87f53976d7405b0c0cac628fc669a741d50063c557e8f50",
+        );
+        assert_eq!(
+            hash("Streebog-512", &[&m2]),
+            "1e88e62226bfca6f9994f1f2d51569e0daf8475a3b0fe61a5300eee46d961376\
+             035fe83549ada2b8620fcd7c496ce5b33f0cb9dddc2b6460143b03dabac9fb28",
+        );
+    }
+
+    /// GB/T 32905-2016 A.1 and A.2.
+    #[test]
+    fn sm3_gb_t_32905() {
+        assert_eq!(
+            hash("SM3", &[b"abc"]),
+            "66c7f0f462eeedd9d1f2d46bdc10e4e24167c4875cf2f7a2297da02b8f4ba8e0",
+        );
+        assert_eq!(
+            hash("SM3", &[b"abcd".repeat(16).as_slice()]),
+            "debe9ff92275b8a138604889c18e5a4d6fdb70e5387e5765293dcba39c0c5732",
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use crate::algorithm::{Algorithm, Category, Hasher};
+use crate::engine::HashValue;
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+
+static CRC32C: crc::Crc<u32> = crc::Crc::<u32>::new(&crc::CRC_32_ISCSI);
+static CRC64: crc::Crc<u64> = crc::Crc::<u64>::new(&crc::CRC_64_XZ);
+
+/// Non-cryptographic checksum. Values are rendered big-endian like `cksum`, SFV and `xxhsum`.
+pub trait Checksum: Send + 'static {
+    const OUTPUT_SIZE: usize;
+
+    fn new() -> Self;
+
+    fn update(&mut self, data: &[u8]);
+
+    fn finalize(self) -> Vec<u8>;
+}
+
+pub struct ChecksumAlgorithm<C> {
+    name: &'static str,
+    _checksum: PhantomData<fn() -> C>,
+}
+
+impl<C> ChecksumAlgorithm<C> {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            _checksum: PhantomData,
+        }
+    }
+}
+
+impl<C> Debug for ChecksumAlgorithm<C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChecksumAlgorithm")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+impl<C: Checksum> Algorithm for ChecksumAlgorithm<C> {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn output_size(&self) -> usize {
+        C::OUTPUT_SIZE
+    }
+
+    fn category(&self) -> Category {
+        Category::Checksum
+    }
+
+    fn hasher(&self) -> Box<dyn Hasher> {
+        Box::new(ChecksumHasher(C::new()))
+    }
+}
+
+struct ChecksumHasher<C>(C);
+
+impl<C: Checksum> Hasher for ChecksumHasher<C> {
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self: Box<Self>) -> HashValue {
+        HashValue::new(self.0.finalize())
+    }
+}
+
+/// CRC-32/ISO-HDLC used by zip, gzip and SFV.
+pub struct Crc32(crc32fast::Hasher);
+
+impl Checksum for Crc32 {
+    const OUTPUT_SIZE: usize = 4;
+
+    fn new() -> Self {
+        Self(crc32fast::Hasher::new())
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.0.finalize().to_be_bytes().to_vec()
+    }
+}
+
+/// CRC-32/ISCSI (Castagnoli).
+pub struct Crc32c(crc::Digest<'static, u32>);
+
+impl Checksum for Crc32c {
+    const OUTPUT_SIZE: usize = 4;
+
+    fn new() -> Self {
+        Self(CRC32C.digest())
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.0.finalize().to_be_bytes().to_vec()
+    }
+}
+
+/// CRC-64/XZ, the ECMA-182 polynomial as used by xz.
+pub struct Crc64(crc::Digest<'static, u64>);
+
+impl Checksum for Crc64 {
+    const OUTPUT_SIZE: usize = 8;
+
+    fn new() -> Self {
+        Self(CRC64.digest())
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.0.finalize().to_be_bytes().to_vec()
+    }
+}
+
+pub struct Adler32(adler2::Adler32);
+
+impl Checksum for Adler32 {
+    const OUTPUT_SIZE: usize = 4;
+
+    fn new() -> Self {
+        Self(adler2::Adler32::new())
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.write_slice(data);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.0.checksum().to_be_bytes().to_vec()
+    }
+}
+
+pub struct Xxh64(xxhash_rust::xxh64::Xxh64);
+
+impl Checksum for Xxh64 {
+    const OUTPUT_SIZE: usize = 8;
+
+    fn new() -> Self {
+        Self(xxhash_rust::xxh64::Xxh64::new(0))
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.0.digest().to_be_bytes().to_vec()
+    }
+}
+
+/// 64-bit XXH3.
+pub struct Xxh3(xxhash_rust::xxh3::Xxh3);
+
+impl Checksum for Xxh3 {
+    const OUTPUT_SIZE: usize = 8;
+
+    fn new() -> Self {
+        Self(xxhash_rust::xxh3::Xxh3::new())
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.0.digest().to_be_bytes().to_vec()
+    }
+}
+
+pub struct Xxh128(xxhash_rust::xxh3::Xxh3);
+
+impl Checksum for Xxh128 {
+    const OUTPUT_SIZE: usize = 16;
+
+    fn new() -> Self {
+        Self(xxhash_rust::xxh3::Xxh3::new())
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.0.digest128().to_be_bytes().to_vec()
+    }
+}
@@ -0,0 +1,383 @@
+use crate::algorithm::{Algorithm, Category, Hasher, ParamLimits, Params};
+use crate::engine::HashValue;
+use crate::prelude::*;
+use hmac::{Mac, SimpleHmac};
+use sha2::digest::core_api::{BlockSizeUser, CoreWrapper};
+use sha2::digest::{Digest, ExtendableOutput, Update};
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+/// HMAC (RFC 2104) over any [`Digest`].
+pub struct HmacAlgorithm<D> {
+    name: &'static str,
+    key: Vec<u8>,
+    _digest: PhantomData<fn() -> D>,
+}
+
+impl<D> HmacAlgorithm<D> {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            key: vec![],
+            _digest: PhantomData,
+        }
+    }
+}
+
+impl<D> Debug for HmacAlgorithm<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HmacAlgorithm")
+            .field("name", &self.name)
+            .field("key", &format_args!("<{} bytes>", self.key.len()))
+            .finish()
+    }
+}
+
+impl<D: Digest + BlockSizeUser + Send + 'static> Algorithm for HmacAlgorithm<D> {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn output_size(&self) -> usize {
+        <D as Digest>::output_size()
+    }
+
+    fn category(&self) -> Category {
+        Category::Mac
+    }
+
+    fn param_limits(&self) -> ParamLimits {
+        ParamLimits {
+            key: usize::MAX,
+            ..ParamLimits::default()
+        }
+    }
+
+    fn params(&self) -> Params {
+        Params {
+            key: self.key.clone(),
+            ..Params::default()
+        }
+    }
+
+    fn with_params(&self, params: &Params) -> Fallible<Arc<dyn Algorithm>> {
+        ensure!(
+            params.output_size.is_none()
+                && params.salt.is_empty()
+                && params.personalization.is_empty(),
+            "{} only accepts a key",
+            self.name,
+        );
+
+        Ok(Arc::new(Self {
+            name: self.name,
+            key: params.key.clone(),
+            _digest: PhantomData,
+        }))
+    }
+
+    fn hasher(&self) -> Box<dyn Hasher> {
+        Box::new(HmacHasher(
+            SimpleHmac::<D>::new_from_slice(&self.key).expect("HMAC accepts any key length"),
+        ))
+    }
+}
+
+struct HmacHasher<D: Digest + BlockSizeUser>(SimpleHmac<D>);
+
+impl<D: Digest + BlockSizeUser + Send> Hasher for HmacHasher<D> {
+    fn update(&mut self, data: &[u8]) {
+        Mac::update(&mut self.0, data);
+    }
+
+    fn finalize(self: Box<Self>) -> HashValue {
+        HashValue::new(self.0.finalize().into_bytes().to_vec())
+    }
+}
+
+/// KMAC128/KMAC256 (NIST SP 800-185). The personalization parameter is the customization string.
+pub struct KmacAlgorithm {
+    name: &'static str,
+    variant: KmacVariant,
+    output_size: usize,
+    key: Vec<u8>,
+    customization: Vec<u8>,
+}
+
+impl KmacAlgorithm {
+    const MAX_OUTPUT_SIZE: usize = 1024;
+
+    pub const fn new128() -> Self {
+        Self {
+            name: "KMAC128",
+            variant: KmacVariant::Kmac128,
+            output_size: 32,
+            key: vec![],
+            customization: vec![],
+        }
+    }
+
+    pub const fn new256() -> Self {
+        Self {
+            name: "KMAC256",
+            variant: KmacVariant::Kmac256,
+            output_size: 64,
+            key: vec![],
+            customization: vec![],
+        }
+    }
+}
+
+impl Debug for KmacAlgorithm {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KmacAlgorithm")
+            .field("name", &self.name)
+            .field("output_size", &self.output_size)
+            .field("key", &format_args!("<{} bytes>", self.key.len()))
+            .field("customization", &self.customization)
+            .finish()
+    }
+}
+
+impl Algorithm for KmacAlgorithm {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn label(&self) -> String {
+        let mut label = format!("{}/{}", self.name, self.output_size * 8);
+        if !self.customization.is_empty() {
+            label.push_str(&format!(
+                " personal={}",
+                HashValue::new(self.customization.clone())
+            ));
+        }
+        label
+    }
+
+    fn output_size(&self) -> usize {
+        self.output_size
+    }
+
+    fn category(&self) -> Category {
+        Category::Mac
+    }
+
+    fn output_size_range(&self) -> Option<RangeInclusive<usize>> {
+        Some(1..=Self::MAX_OUTPUT_SIZE)
+    }
+
+    fn param_limits(&self) -> ParamLimits {
+        ParamLimits {
+            key: usize::MAX,
+            personalization: usize::MAX,
+            ..ParamLimits::default()
+        }
+    }
+
+    fn params(&self) -> Params {
+        Params {
+            output_size: Some(self.output_size),
+            key: self.key.clone(),
+            salt: vec![],
+            personalization: self.customization.clone(),
+        }
+    }
+
+    fn with_params(&self, params: &Params) -> Fallible<Arc<dyn Algorithm>> {
+        ensure!(params.salt.is_empty(), "{} has no salt", self.name);
+
+        let output_size = params.output_size.unwrap_or(self.output_size);
+        ensure!(
+            (1..=Self::MAX_OUTPUT_SIZE).contains(&output_size),
+            "{} output size must be 1-{} bytes",
+            self.name,
+            Self::MAX_OUTPUT_SIZE,
+        );
+
+        Ok(Arc::new(Self {
+            name: self.name,
+            variant: self.variant,
+            output_size,
+            key: params.key.clone(),
+            customization: params.personalization.clone(),
+        }))
+    }
+
+    fn hasher(&self) -> Box<dyn Hasher> {
+        let mut xof = self.variant.cshake(&self.customization);
+
+        // bytepad(encode_string(K), rate)
+        let rate = self.variant.rate();
+        let mut buf = Vec::with_capacity(rate * 2);
+        buf.extend(left_encode(rate as u64));
+        buf.extend(left_encode(self.key.len() as u64 * 8));
+        buf.extend(&self.key);
+        buf.resize(buf.len().next_multiple_of(rate), 0);
+        xof.update(&buf);
+
+        Box::new(KmacHasher {
+            xof,
+            output_size: self.output_size,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum KmacVariant {
+    Kmac128,
+    Kmac256,
+}
+
+impl KmacVariant {
+    /// cSHAKE rate in bytes.
+    fn rate(self) -> usize {
+        match self {
+            KmacVariant::Kmac128 => <sha3::CShake128Core as BlockSizeUser>::block_size(),
+            KmacVariant::Kmac256 => <sha3::CShake256Core as BlockSizeUser>::block_size(),
+        }
+    }
+
+    fn cshake(self, customization: &[u8]) -> Box<dyn KmacXof> {
+        match self {
+            KmacVariant::Kmac128 => Box::new(CoreWrapper::from_core(
+                sha3::CShake128Core::new_with_function_name(b"KMAC", customization),
+            )),
+            KmacVariant::Kmac256 => Box::new(CoreWrapper::from_core(
+                sha3::CShake256Core::new_with_function_name(b"KMAC", customization),
+            )),
+        }
+    }
+}
+
+trait KmacXof: Send {
+    fn update(&mut self, data: &[u8]);
+
+    fn finalize_into(self: Box<Self>, out: &mut [u8]);
+}
+
+impl<D: Update + ExtendableOutput + Send> KmacXof for D {
+    fn update(&mut self, data: &[u8]) {
+        Update::update(self, data);
+    }
+
+    fn finalize_into(self: Box<Self>, out: &mut [u8]) {
+        self.finalize_xof_into(out);
+    }
+}
+
+struct KmacHasher {
+    xof: Box<dyn KmacXof>,
+    output_size: usize,
+}
+
+impl Hasher for KmacHasher {
+    fn update(&mut self, data: &[u8]) {
+        self.xof.update(data);
+    }
+
+    fn finalize(mut self: Box<Self>) -> HashValue {
+        self.xof.update(&right_encode(self.output_size as u64 * 8));
+        let mut buf = vec![0; self.output_size];
+        self.xof.finalize_into(&mut buf);
+        HashValue::new(buf)
+    }
+}
+
+fn encode(value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let skip = bytes.iter().take_while(|data| **data == 0).count().min(7);
+    bytes[skip..].to_vec()
+}
+
+fn left_encode(value: u64) -> Vec<u8> {
+    let mut ret = encode(value);
+    ret.insert(0, ret.len() as u8);
+    ret
+}
+
+fn right_encode(value: u64) -> Vec<u8> {
+    let mut ret = encode(value);
+    ret.push(ret.len() as u8);
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// NIST SP 800-185 KMAC samples.
+    fn kmac(algorithm: KmacAlgorithm, data: &[u8], customization: &[u8]) -> String {
+        let params = Params {
+            key: (0x40..=0x5f).collect(),
+            personalization: customization.to_vec(),
+            ..algorithm.params()
+        };
+        let mut hasher = algorithm.with_params(&params).unwrap().hasher();
+        hasher.update(data);
+        hasher.finalize().to_string()
+    }
+
+    #[test]
+    fn kmac_sample_1() {
+        assert_eq!(
+            kmac(KmacAlgorithm::new128(), &[0, 1, 2, 3], b""),
+            "e5780b0d3ea6f7d3a429c5706aa43a00fadbd7d49628839e3187243f456ee14e",
+        );
+    }
+
+    #[test]
+    fn kmac_sample_2() {
+        assert_eq!(
+            kmac(
+                KmacAlgorithm::new128(),
+                &[0, 1, 2, 3],
+                b"My Tagged Application"
+            ),
+            "3b1fba963cd8b0b59e8c1a6d71888b7143651af8ba0a7070c0979e2811324aa5",
+        );
+    }
+
+    #[test]
+    fn kmac_sample_4() {
+        assert_eq!(
+            kmac(
+                KmacAlgorithm::new256(),
+                &[0, 1, 2, 3],
+                b"My Tagged Application"
+            ),
+            "20c570c31346f703c9ac36c61c03cb64c3970d0cfc787e9b79599d273a68d2f7\
+             f69d4cc3de9d104a351689f27cf6f5951f0103f33f4f24871024d9c27773a8dd",
+        );
+    }
+
+    #[test]
+    fn kmac_sample_6() {
+        assert_eq!(
+            kmac(
+                KmacAlgorithm::new256(),
+                &(0..=0xc7).collect::<Vec<u8>>(),
+                b"My Tagged Application"
+            ),
+            "b58618f71f92e1d56c1b8c55ddd7cd188b97b4ca4d99831eb2699a837da2e4d9\
+             70fbacfde50033aea585f1a2708510c32d07880801bd182898fe476876fc8965",
+        );
+    }
+
+    #[test]
+    fn integer_encoding() {
+        assert_eq!(left_encode(0), [1, 0]);
+        assert_eq!(left_encode(168), [1, 168]);
+        assert_eq!(left_encode(256), [2, 1, 0]);
+        assert_eq!(right_encode(0), [0, 1]);
+        assert_eq!(right_encode(512), [2, 0, 2]);
+    }
+
+    #[test]
+    fn rate() {
+        assert_eq!(KmacVariant::Kmac128.rate(), 168);
+        assert_eq!(KmacVariant::Kmac256.rate(), 136);
+    }
+}
@@ -0,0 +1,323 @@
+//! Checksum files written by GNU coreutils `sha256sum`, `md5sum`, `b2sum` and friends.
+//!
+//! ```text
+//! <hex digest>  <name>
+//! <hex digest> *<name>
+//! ```
+//!
+//! `*` marks the binary mode, which makes no difference on POSIX systems. A line starting with `\`
+//! has `\\`, `\n` and `\r` escaped in the name. The BSD style written by `--tag` is not supported.
+
+use crate::algorithm::{Algorithm, Params, Registry};
+use crate::engine::HashValue;
+use crate::prelude::*;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Checksum files are tiny, and this keeps a large file from being read twice on drop.
+const MAX_SIZE: u64 = 1024 * 1024;
+
+/// Algorithms named by a word of the file name with a `sum` or `sums` suffix removed, e.g.
+/// `SHA256SUMS`, `image.iso.sha256` or `sha3-256sums.txt`.
+const NAME_HINTS: [(&str, &str); 12] = [
+    ("md5", "MD5"),
+    ("sha1", "SHA1"),
+    ("sha224", "SHA224"),
+    ("sha256", "SHA256"),
+    ("sha384", "SHA384"),
+    ("sha512", "SHA512"),
+    ("sha3-224", "SHA3-224"),
+    ("sha3-256", "SHA3-256"),
+    ("sha3-384", "SHA3-384"),
+    ("sha3-512", "SHA3-512"),
+    ("b2", "BLAKE2b"),
+    ("b3", "BLAKE3"),
+];
+
+/// Algorithms of the digest lengths written by only one of `md5sum`, `sha1sum`, `sha224sum`,
+/// `sha256sum`, `sha384sum`, `sha512sum` and `b2sum` by default. 64 bytes is written by both
+/// `sha512sum` and `b2sum`, so it needs a name hint.
+const SIZE_HINTS: [(usize, &str); 5] = [
+    (16, "MD5"),
+    (20, "SHA1"),
+    (28, "SHA224"),
+    (32, "SHA256"),
+    (48, "SHA384"),
+];
+
+#[derive(Clone, Debug)]
+pub struct ChecksumFile {
+    pub algorithm: Arc<dyn Algorithm>,
+    /// In the file order.
+    pub lines: Vec<ChecksumLine>,
+    /// Non-blank lines skipped like `sha256sum -c` does.
+    pub improperly_formatted: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct ChecksumLine {
+    /// As written in the file.
+    pub name: String,
+    /// Resolved relative to the checksum file.
+    pub pathname: PathBuf,
+    pub expected: HashValue,
+}
+
+/// Returns `None` unless `pathname` is a small regular file in which most non-blank lines are
+/// checksums, and fails if the algorithm is ambiguous.
+pub fn parse(pathname: &Path, registry: &Registry) -> Fallible<Option<ChecksumFile>> {
+    // a FIFO would block until written.
+    let metadata = std::fs::metadata(pathname)?;
+    if !metadata.is_file() || MAX_SIZE < metadata.len() {
+        return Ok(None);
+    }
+    match String::from_utf8(std::fs::read(pathname)?) {
+        Ok(contents) => parse_contents(pathname, &contents, registry),
+        Err(_) => Ok(None),
+    }
+}
+
+fn parse_contents(
+    pathname: &Path,
+    contents: &str,
+    registry: &Registry,
+) -> Fallible<Option<ChecksumFile>> {
+    let parent = pathname.parent().unwrap_or(Path::new(""));
+    let mut lines = vec![];
+    let mut improperly_formatted = 0;
+    for line in contents.lines().filter(|data| !data.trim().is_empty()) {
+        match parse_line(line) {
+            Some((expected, name)) => lines.push(ChecksumLine {
+                pathname: parent.join(&name),
+                name,
+                expected,
+            }),
+            None => improperly_formatted += 1,
+        }
+    }
+    if lines.len() <= improperly_formatted {
+        return Ok(None);
+    }
+
+    // the most common length, so that a few broken lines do not decide the algorithm.
+    let mut sizes = HashMap::<usize, usize>::new();
+    for data in &lines {
+        *sizes.entry(data.expected.as_bytes().len()).or_default() += 1;
+    }
+    let size = sizes
+        .into_iter()
+        .max_by_key(|(size, count)| (*count, std::cmp::Reverse(*size)))
+        .map(|(size, _)| size)
+        .unwrap_or_default();
+
+    let filename = pathname
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_lowercase();
+    let mut hints = name_hints(&filename);
+    let algorithm = match (hints.next(), hints.next()) {
+        (Some(name), None) => configure(registry, name, size)
+            .with_context(|| format!("{filename} names {name} but has {size} byte checksums"))?,
+        (Some(name), Some(other)) => bail!("{filename} names both {name} and {other}"),
+        (None, _) => SIZE_HINTS
+            .iter()
+            .find(|(data, _)| *data == size)
+            .and_then(|(_, name)| registry.find(name))
+            .with_context(|| {
+                format!("{filename} has no algorithm in the name for {size} byte checksums")
+            })?,
+    };
+    debug!(?pathname, algorithm = algorithm.name(), size, "checksum");
+
+    let (lines, mismatched) = lines
+        .into_iter()
+        .partition::<Vec<_>, _>(|data| data.expected.as_bytes().len() == size);
+    Ok(Some(ChecksumFile {
+        algorithm,
+        lines,
+        improperly_formatted: improperly_formatted + mismatched.len(),
+    }))
+}
+
+/// Distinct algorithms named by the words of `filename`, which is in lower case.
+fn name_hints(filename: &str) -> impl Iterator<Item = &'static str> {
+    let mut ret = vec![];
+    for data in filename.split(['.', '_', ' ']) {
+        // every run of `-` separated parts, since a hint may contain `-` itself.
+        let parts = data.split('-').collect::<Vec<_>>();
+        for start in 0..parts.len() {
+            for end in start + 1..=parts.len() {
+                let word = parts[start..end].join("-");
+                let word = word
+                    .strip_suffix("sums")
+                    .or_else(|| word.strip_suffix("sum"))
+                    .unwrap_or(&word);
+                if let Some((_, name)) = NAME_HINTS.iter().find(|(hint, _)| *hint == word)
+                    && !ret.contains(name)
+                {
+                    ret.push(*name);
+                }
+            }
+        }
+    }
+    ret.into_iter()
+}
+
+/// Returns `name` producing `size` bytes, e.g. for `b2sum -l 256`.
+fn configure(registry: &Registry, name: &str, size: usize) -> Option<Arc<dyn Algorithm>> {
+    let algorithm = registry.find(name)?;
+    if algorithm.output_size() == size {
+        return Some(algorithm);
+    }
+    if !algorithm
+        .output_size_range()
+        .is_some_and(|data| data.contains(&size))
+    {
+        return None;
+    }
+    let params = Params {
+        output_size: Some(size),
+        ..Default::default()
+    };
+    registry
+        .configure(name, &params)
+        .inspect_err(|e| warn!(?e, "configure"))
+        .ok()
+}
+
+fn parse_line(line: &str) -> Option<(HashValue, String)> {
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    let (escaped, line) = match line.strip_prefix('\\') {
+        Some(data) => (true, data),
+        None => (false, line),
+    };
+
+    let (hex, rest) = line.split_once(' ')?;
+    let name = rest.strip_prefix([' ', '*'])?;
+    if hex.is_empty() || name.is_empty() {
+        return None;
+    }
+    let expected = HashValue::new(hex::decode(hex).ok()?);
+
+    let name = if escaped {
+        unescape(name)?
+    } else {
+        name.to_owned()
+    };
+    Some((expected, name))
+}
+
+fn unescape(data: &str) -> Option<String> {
+    let mut ret = String::with_capacity(data.len());
+    let mut chars = data.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            ret.push(c);
+            continue;
+        }
+        match chars.next()? {
+            '\\' => ret.push('\\'),
+            'n' => ret.push('\n'),
+            'r' => ret.push('\r'),
+            _ => return None,
+        }
+    }
+    Some(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHA256: &str = "ca978112ca1bbdcafac231b39a23dc4da786eff8147c4e72b9807785afee48bb";
+    const SHA512: &str = "1f40fc92da241694750979ee6cf582f2d5d7d28e18335de05abc54d0560e0f53\
+                          02860c652bf08d560252aa5e74210546f369fbbbce8c12cfc7957b2652fe9a75";
+
+    fn parse(filename: &str, contents: &str) -> Fallible<Option<ChecksumFile>> {
+        parse_contents(
+            &Path::new("/checksums").join(filename),
+            contents,
+            &Registry::default(),
+        )
+    }
+
+    fn algorithm(filename: &str, contents: &str) -> String {
+        parse(filename, contents)
+            .unwrap()
+            .unwrap()
+            .algorithm
+            .label()
+    }
+
+    #[test]
+    fn formats() {
+        let data = parse(
+            "SHA256SUMS",
+            &format!("{SHA256}  a.txt\n{SHA256} *b c.txt\n\\{SHA256}  d\\\\e\\nf\n"),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            data.lines
+                .iter()
+                .map(|data| data.name.as_str())
+                .collect::<Vec<_>>(),
+            ["a.txt", "b c.txt", "d\\e\nf"],
+        );
+        assert_eq!(data.lines[0].pathname, Path::new("/checksums/a.txt"));
+        assert_eq!(data.lines[0].expected.to_string(), SHA256);
+        assert_eq!(data.improperly_formatted, 0);
+    }
+
+    #[test]
+    fn name_hint() {
+        let line = format!("{SHA256}  a.txt\n");
+        assert_eq!(algorithm("SHA256SUMS", &line), "SHA256");
+        assert_eq!(algorithm("image.iso.sha256", &line), "SHA256");
+        assert_eq!(algorithm("SHA3-256SUMS", &line), "SHA3-256");
+        assert_eq!(
+            algorithm("ubuntu-24.04-sha3-256sums.txt", &line),
+            "SHA3-256"
+        );
+        assert_eq!(algorithm("B3SUMS", &line), "BLAKE3");
+        assert_eq!(algorithm("B2SUMS", &line), "BLAKE2b-256");
+        // not a word of the name.
+        assert_eq!(algorithm("lib2b3.txt", &line), "SHA256");
+    }
+
+    #[test]
+    fn ambiguous() {
+        let line = format!("{SHA512}  a.txt\n");
+        assert_eq!(algorithm("SHA512SUMS", &line), "SHA512");
+        assert_eq!(algorithm("B2SUMS", &line), "BLAKE2b-512");
+        assert!(parse("checksums.txt", &line).is_err());
+        assert!(parse("a.md5.sha256", &format!("{SHA256}  a.txt\n")).is_err());
+        // the hint does not match the length.
+        assert!(parse("MD5SUMS", &format!("{SHA256}  a.txt\n")).is_err());
+    }
+
+    #[test]
+    fn improperly_formatted() {
+        let data = parse(
+            "SHA256SUMS",
+            &format!(
+                "{SHA256}  a.txt\nbroken\n\n{SHA256}  b.txt\n{}  c.txt\n",
+                &SHA256[..32]
+            ),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(data.lines.len(), 2);
+        assert_eq!(data.improperly_formatted, 2);
+
+        // mostly not checksums.
+        assert!(
+            parse("notes.txt", &format!("{SHA256}  a.txt\nfoo\nbar\n"))
+                .unwrap()
+                .is_none()
+        );
+        assert!(parse("empty.txt", "").unwrap().is_none());
+    }
+}
@@ -0,0 +1,160 @@
+//! Deterministic digest of a directory tree, similar to Go's `h1:` module hashes.
+//!
+//! The digest is the hash of a manifest with one line per regular file, sorted by the relative
+//! path compared bytewise:
+//!
+//! ```text
+//! <mode> <hex digest of the contents>  <relative path with / separators>\n
+//! ```
+//!
+//! `<mode>` is `100755` for files executable by the owner (`0o100`) and `100644` otherwise, like
+//! git, so that ownership, timestamps and the other permission bits do not affect the digest. Both
+//! the file digests and the manifest digest use the same algorithm.
+//!
+//! The manifest only has the files listed with the [`WalkOptions`], so digests are only comparable
+//! if made with the same options.
+
+use crate::algorithm::Algorithm;
+use crate::engine::HashValue;
+use crate::prelude::*;
+use crate::walk::{self, WalkOptions};
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug)]
+pub struct DirectoryFile {
+    pub pathname: PathBuf,
+    /// Relative to the root with `/` separators.
+    pub relative: String,
+    pub executable: bool,
+}
+
+/// Lists the files of the manifest in order. Fails on any walk error since a skipped directory
+/// would change the digest without notice.
+pub fn list(root: &Path, options: &WalkOptions) -> Fallible<Vec<DirectoryFile>> {
+    let options = WalkOptions {
+        strict: true,
+        ..options.clone()
+    };
+    let mut ret = walk::walk(root, &options)?
+        .into_iter()
+        .map(|pathname| {
+            let relative = pathname
+                .strip_prefix(root)?
+                .to_str()
+                .with_context(|| format!("non UTF-8 path {}", pathname.display()))?
+                .replace(std::path::MAIN_SEPARATOR, "/");
+            // a newline would forge another line of the manifest.
+            ensure!(!relative.contains('\n'), "newline in {relative:?}");
+
+            let metadata = std::fs::metadata(&pathname)
+                .with_context(|| format!("metadata {}", pathname.display()))?;
+            Ok(DirectoryFile {
+                executable: is_executable(&metadata),
+                pathname,
+                relative,
+            })
+        })
+        .collect::<Fallible<Vec<_>>>()?;
+    ret.sort_by(|a, b| a.relative.cmp(&b.relative));
+    Ok(ret)
+}
+
+/// `files` must be in the order of [`list`] and each value hashed with `algorithm`.
+pub fn digest<'a>(
+    algorithm: &dyn Algorithm,
+    files: impl IntoIterator<Item = (&'a DirectoryFile, &'a HashValue)>,
+) -> HashValue {
+    let mut hasher = algorithm.hasher();
+    for (file, value) in files {
+        let mode = if file.executable { "100755" } else { "100644" };
+        hasher.update(format!("{mode} {value}  {}\n", file.relative).as_bytes());
+    }
+    hasher.finalize()
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    metadata.permissions().mode() & 0o100 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &std::fs::Metadata) -> bool {
+    false
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::algorithm::Registry;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let ret = std::env::temp_dir().join(format!("hash-gui-{name}-{}", std::process::id()));
+        if ret.exists() {
+            std::fs::remove_dir_all(&ret).unwrap();
+        }
+        std::fs::create_dir_all(ret.join("sub")).unwrap();
+        ret
+    }
+
+    /// Matches the manifest hashed by `sha256sum` in a shell.
+    #[test]
+    fn digest_manifest() {
+        let root = temp_dir("dirhash");
+        std::fs::write(root.join("b.txt"), "a").unwrap();
+        // not executable by the owner, which is all git checks.
+        std::fs::set_permissions(root.join("b.txt"), std::fs::Permissions::from_mode(0o655))
+            .unwrap();
+        std::fs::write(root.join("sub/x.sh"), "hello").unwrap();
+        std::fs::set_permissions(
+            root.join("sub/x.sh"),
+            std::fs::Permissions::from_mode(0o755),
+        )
+        .unwrap();
+        std::fs::write(root.join(".hidden"), "z").unwrap();
+
+        let files = list(&root, &WalkOptions::default()).unwrap();
+        assert_eq!(
+            files
+                .iter()
+                .map(|data| data.relative.as_str())
+                .collect::<Vec<_>>(),
+            ["b.txt", "sub/x.sh"],
+        );
+
+        let algorithm = Registry::default().find("SHA256").unwrap();
+        let values = files
+            .iter()
+            .map(|data| {
+                let mut hasher = algorithm.hasher();
+                hasher.update(&std::fs::read(&data.pathname).unwrap());
+                hasher.finalize()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            digest(algorithm.as_ref(), files.iter().zip(&values)).to_string(),
+            "02061789f77cf1659acc07ce0509a13f4d1cd82226050bcada81b22fff6deed6",
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn fail_on_walk_error() {
+        let root = temp_dir("dirhash-loop");
+        std::fs::write(root.join("a.txt"), "a").unwrap();
+        std::os::unix::fs::symlink(&root, root.join("sub/loop")).unwrap();
+        let options = WalkOptions {
+            follow_symlinks: true,
+            ..Default::default()
+        };
+
+        // the loop is only logged by a plain walk.
+        assert_eq!(walk::walk(&root, &options).unwrap().len(), 1);
+        assert!(list(&root, &options).is_err());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
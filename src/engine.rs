@@ -0,0 +1,380 @@
+use crate::algorithm::Algorithm;
+use crate::prelude::*;
+use bytes::Bytes;
+use std::fmt::{Display, Formatter};
+use std::fs::Metadata;
+use std::ops::ControlFlow;
+use std::path::Path;
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::time::SystemTime;
+
+mod backend;
+mod pool;
+
+pub use backend::ReadBackend;
+
+const CHUNK_SIZE: u64 = 1024 * 1024;
+const CHUNK_QUEUE_SIZE: usize = 10;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Progress {
+    pub processed: u64,
+    /// `None` for streams whose size is unknown until EOF.
+    pub total: Option<u64>,
+}
+
+impl Progress {
+    pub fn percent(&self) -> Option<f32> {
+        match self.total {
+            None => None,
+            Some(0) => Some(100.0),
+            Some(total) => Some(((self.processed as f64) / (total as f64) * 100.0) as f32),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct HashValue(Vec<u8>);
+
+impl HashValue {
+    pub fn new(data: Vec<u8>) -> Self {
+        Self(data)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Display for HashValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for data in &self.0 {
+            write!(f, "{data:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HashResult {
+    pub algorithm: Arc<dyn Algorithm>,
+    pub value: HashValue,
+}
+
+#[derive(Clone, Debug)]
+pub enum HashOutcome {
+    /// One result per requested algorithm, in the same order.
+    Finished(Vec<HashResult>),
+    /// The file changed while it was read, so the results may not match any version of the file.
+    Modified(Vec<HashResult>),
+    /// `on_progress` returned [`ControlFlow::Break`].
+    Cancelled,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum JobState {
+    #[default]
+    Running,
+    Paused,
+    Cancelled,
+}
+
+/// Pauses or cancels a job from another thread. Calling [`JobControl::wait`] from `on_progress`
+/// of [`hash_file`] stops reading while paused since the reader blocks once the chunk queue is
+/// full.
+#[derive(Debug, Default)]
+pub struct JobControl {
+    state: Mutex<JobState>,
+    condvar: Condvar,
+}
+
+impl JobControl {
+    pub fn state(&self) -> JobState {
+        *self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn pause(&self) {
+        self.transit(JobState::Running, JobState::Paused);
+    }
+
+    pub fn resume(&self) {
+        self.transit(JobState::Paused, JobState::Running);
+    }
+
+    pub fn cancel(&self) {
+        *self.state.lock().unwrap_or_else(PoisonError::into_inner) = JobState::Cancelled;
+        self.condvar.notify_all();
+    }
+
+    /// Blocks while paused and breaks once cancelled.
+    pub fn wait(&self) -> ControlFlow<()> {
+        let state = self
+            .condvar
+            .wait_while(
+                self.state.lock().unwrap_or_else(PoisonError::into_inner),
+                |state| *state == JobState::Paused,
+            )
+            .unwrap_or_else(PoisonError::into_inner);
+        match *state {
+            JobState::Cancelled => ControlFlow::Break(()),
+            JobState::Running | JobState::Paused => ControlFlow::Continue(()),
+        }
+    }
+
+    fn transit(&self, from: JobState, to: JobState) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if *state == from {
+            *state = to;
+            self.condvar.notify_all();
+        }
+    }
+}
+
+/// Metadata that changes when the file is written to or replaced.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct FileStamp {
+    len: u64,
+    modified: Option<SystemTime>,
+    #[cfg(unix)]
+    dev: u64,
+    #[cfg(unix)]
+    ino: u64,
+    #[cfg(unix)]
+    ctime: (i64, i64),
+}
+
+impl FileStamp {
+    fn new(metadata: &Metadata) -> Self {
+        #[cfg(unix)]
+        use std::os::unix::fs::MetadataExt;
+
+        Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+            #[cfg(unix)]
+            dev: metadata.dev(),
+            #[cfg(unix)]
+            ino: metadata.ino(),
+            #[cfg(unix)]
+            ctime: (metadata.ctime(), metadata.ctime_nsec()),
+        }
+    }
+}
+
+fn is_fifo(metadata: &Metadata) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+
+        metadata.file_type().is_fifo()
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        false
+    }
+}
+
+/// Returns the size to read, or `None` to read until EOF. Refuses what cannot be hashed as a file.
+fn readable_size(pathname: &Path, metadata: &Metadata) -> Fallible<Option<u64>> {
+    let file_type = metadata.file_type();
+    if file_type.is_dir() {
+        return Err(std::io::Error::from(std::io::ErrorKind::IsADirectory))
+            .with_context(|| format!("open {}", pathname.display()));
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+
+        if file_type.is_block_device() || file_type.is_char_device() || file_type.is_socket() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "devices and sockets are not hashed",
+            ))
+            .with_context(|| format!("open {}", pathname.display()));
+        }
+        if is_fifo(metadata) {
+            return Ok(None);
+        }
+    }
+
+    // pseudo files such as /proc report 0 bytes but have contents.
+    Ok(match metadata.len() {
+        0 => None,
+        len => Some(len),
+    })
+}
+
+/// Hashes the file with a dedicated reader thread and one hasher thread per algorithm, blocking
+/// the caller until finished. Every chunk is read once and shared by all hashers.
+///
+/// `on_progress` is called on the caller thread after every chunk. Fails instead of returning a
+/// digest if the file could not be read up to its size.
+///
+/// The metadata is compared before and after reading to detect the file being modified meanwhile.
+///
+/// Symlinks are followed. FIFOs and empty-looking files are read until EOF with an unknown total,
+/// and devices and sockets are refused.
+pub fn hash_file(
+    pathname: &Path,
+    algorithms: &[Arc<dyn Algorithm>],
+    backend: ReadBackend,
+    mut on_progress: impl FnMut(Progress) -> ControlFlow<()>,
+) -> Fallible<HashOutcome> {
+    // checks the type before opening since opening a device may have side effects.
+    let metadata =
+        std::fs::metadata(pathname).with_context(|| format!("metadata {}", pathname.display()))?;
+    let filesize = readable_size(pathname, &metadata)?;
+    let stamp = FileStamp::new(&metadata);
+    let mut reader = backend::open(pathname, backend, filesize, &stamp)?;
+
+    if on_progress(Progress {
+        processed: 0,
+        total: filesize,
+    })
+    .is_break()
+    {
+        return Ok(HashOutcome::Cancelled);
+    }
+
+    let (tx, rx) = std::sync::mpsc::sync_channel(CHUNK_QUEUE_SIZE);
+
+    std::thread::scope(|scope| {
+        let read_span = info_span!("read", pathname = pathname.display().to_string());
+
+        let reader_handle = scope.spawn(move || -> Fallible<()> {
+            let _guard = read_span.enter();
+
+            let mut read = 0u64;
+
+            loop {
+                let max = match filesize {
+                    Some(filesize) => CHUNK_SIZE.min(filesize - read),
+                    None => CHUNK_SIZE,
+                };
+                if max == 0 {
+                    break;
+                }
+
+                let buf = match reader.read_chunk(max as usize) {
+                    Ok(data) if data.is_empty() && filesize.is_some() => {
+                        Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof))
+                    }
+                    ret => ret,
+                };
+                let buf = match buf {
+                    Ok(data) if data.is_empty() => break,
+                    Ok(data) => data,
+                    Err(e) => {
+                        warn!(?e, "read");
+                        return Err(e).with_context(|| match filesize {
+                            Some(filesize) => format!(
+                                "read {} at {read} of {filesize} bytes",
+                                pathname.display(),
+                            ),
+                            None => format!("read {} at {read} bytes", pathname.display()),
+                        });
+                    }
+                };
+
+                read += buf.len() as u64;
+                if let Err(e) = tx.send(buf) {
+                    info!(?e, "disconnected");
+                    return Ok(());
+                }
+            }
+
+            info!("finish");
+            Ok(())
+        });
+
+        let hashers = algorithms
+            .iter()
+            .map(|algorithm| {
+                let (tx, rx) = std::sync::mpsc::sync_channel::<Bytes>(CHUNK_QUEUE_SIZE);
+                let hash_span = info_span!(
+                    "hash",
+                    pathname = pathname.display().to_string(),
+                    algorithm = algorithm.name(),
+                );
+                let handle = scope.spawn(move || {
+                    let _guard = hash_span.enter();
+
+                    let mut hasher = algorithm.hasher();
+                    for data in rx {
+                        hasher.update(&data);
+                    }
+
+                    info!("finish");
+                    hasher.finalize()
+                });
+                (tx, handle)
+            })
+            .collect::<Vec<_>>();
+
+        let mut processed = 0u64;
+
+        for data in rx {
+            processed += data.len() as u64;
+
+            for (tx, _) in &hashers {
+                // the receiver only goes away if the hasher panicked, which is reported on join.
+                tx.send(data.clone()).ok();
+            }
+
+            if on_progress(Progress {
+                processed,
+                total: filesize,
+            })
+            .is_break()
+            {
+                info!("cancelled");
+                return Ok(HashOutcome::Cancelled);
+            }
+        }
+
+        let (senders, handles) = hashers.into_iter().unzip::<_, _, Vec<_>, Vec<_>>();
+        drop(senders);
+
+        // never reports a digest of a truncated read.
+        reader_handle
+            .join()
+            .map_err(|_| anyhow!("reader panicked"))??;
+        if let Some(filesize) = filesize {
+            ensure!(
+                processed == filesize,
+                "read {} of {filesize} bytes from {}",
+                processed,
+                pathname.display(),
+            );
+        }
+
+        let results = handles
+            .into_iter()
+            .zip(algorithms)
+            .map(|(handle, algorithm)| {
+                let value = handle
+                    .join()
+                    .map_err(|_| anyhow!("{} hasher panicked", algorithm.name()))?;
+                Ok(HashResult {
+                    algorithm: algorithm.clone(),
+                    value,
+                })
+            })
+            .collect::<Fallible<Vec<_>>>()?;
+
+        let current_stamp = FileStamp::new(
+            &std::fs::metadata(pathname)
+                .with_context(|| format!("metadata {}", pathname.display()))?,
+        );
+        // writing to a FIFO updates its timestamps.
+        if stamp != current_stamp && !is_fifo(&metadata) {
+            warn!(?stamp, ?current_stamp, "modified while hashing");
+            return Ok(HashOutcome::Modified(results));
+        }
+
+        Ok(HashOutcome::Finished(results))
+    })
+}
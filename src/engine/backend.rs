@@ -0,0 +1,248 @@
+use super::pool::BufferPool;
+use super::{CHUNK_SIZE, FileStamp};
+use crate::prelude::*;
+use bytes::Bytes;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::BufReader;
+use std::io::prelude::*;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+const READER_CAPACITY: usize = 8 * 1024 * 1024;
+
+/// A file modified this recently may still be written to, and is not mapped.
+const MMAP_SETTLE_TIME: Duration = Duration::from_secs(2);
+
+/// How the file contents are read.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ReadBackend {
+    /// `read` through a large userspace buffer.
+    #[default]
+    Buffered,
+    /// Maps the file and shares the pages with the hashers without copying. Falls back to
+    /// [`ReadBackend::Buffered`] for streams and files changed recently or since the metadata was
+    /// read.
+    ///
+    /// The process is killed by `SIGBUS` if the file is truncated while mapped, so this is unsafe
+    /// for files that may still be written to.
+    Mmap,
+    /// Bypasses the page cache so that hashing a huge file does not evict everything else. Uses
+    /// `O_DIRECT`, or `posix_fadvise(DONTNEED)` where it is not supported, on Linux and is the
+    /// same as [`ReadBackend::Buffered`] elsewhere.
+    Direct,
+}
+
+impl ReadBackend {
+    pub const ALL: [ReadBackend; 3] = [
+        ReadBackend::Buffered,
+        ReadBackend::Mmap,
+        ReadBackend::Direct,
+    ];
+}
+
+impl Display for ReadBackend {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            ReadBackend::Buffered => "Buffered",
+            ReadBackend::Mmap => "mmap",
+            ReadBackend::Direct => "Direct I/O",
+        })
+    }
+}
+
+pub(super) trait ChunkReader: Send {
+    /// Reads up to `max` bytes, fewer only at EOF. Returns empty at EOF.
+    fn read_chunk(&mut self, max: usize) -> std::io::Result<Bytes>;
+}
+
+/// `filesize` is `None` for streams. `stamp` is of the metadata `filesize` came from.
+pub(super) fn open(
+    pathname: &Path,
+    backend: ReadBackend,
+    filesize: Option<u64>,
+    stamp: &FileStamp,
+) -> Fallible<Box<dyn ChunkReader>> {
+    let open = || File::open(pathname).with_context(|| format!("open {}", pathname.display()));
+
+    Ok(match (backend, filesize) {
+        (ReadBackend::Buffered, _) | (ReadBackend::Mmap | ReadBackend::Direct, None) => {
+            Box::new(BufferedReader::new(open()?))
+        }
+        (ReadBackend::Mmap, Some(_)) => {
+            let file = open()?;
+            match MmapReader::new(pathname, &file, stamp)? {
+                Some(reader) => Box::new(reader),
+                None => Box::new(BufferedReader::new(file)),
+            }
+        }
+        #[cfg(target_os = "linux")]
+        (ReadBackend::Direct, Some(_)) => Box::new(DirectReader::open(pathname)?),
+        #[cfg(not(target_os = "linux"))]
+        (ReadBackend::Direct, Some(_)) => Box::new(BufferedReader::new(open()?)),
+    })
+}
+
+struct BufferedReader {
+    reader: BufReader<File>,
+    pool: Arc<BufferPool>,
+}
+
+impl BufferedReader {
+    fn new(file: File) -> Self {
+        Self {
+            reader: BufReader::with_capacity(READER_CAPACITY, file),
+            pool: BufferPool::new(CHUNK_SIZE as usize, 1),
+        }
+    }
+}
+
+impl ChunkReader for BufferedReader {
+    fn read_chunk(&mut self, max: usize) -> std::io::Result<Bytes> {
+        let mut buf = self.pool.take();
+        let len = fill(&mut self.reader, &mut buf.as_mut_slice()[..max])?;
+        Ok(buf.freeze(len))
+    }
+}
+
+struct MmapReader {
+    data: Bytes,
+    pos: usize,
+}
+
+impl MmapReader {
+    /// Returns `None` for a file that looks like it is being written, since the process gets
+    /// SIGBUS if the file is truncated while mapped.
+    fn new(pathname: &Path, file: &File, stamp: &FileStamp) -> Fallible<Option<Self>> {
+        let is_settled = || -> Fallible<bool> {
+            let metadata = file
+                .metadata()
+                .with_context(|| format!("metadata {}", pathname.display()))?;
+            let settled = metadata
+                .modified()
+                .ok()
+                .and_then(|data| SystemTime::now().duration_since(data).ok())
+                .is_some_and(|data| MMAP_SETTLE_TIME <= data);
+            Ok(settled && FileStamp::new(&metadata) == *stamp)
+        };
+
+        if !is_settled()? {
+            info!(pathname = %pathname.display(), "changed recently, not mapped");
+            return Ok(None);
+        }
+        // SAFETY: the file has not changed for a while. It may still be truncated by another
+        // process, which cannot be prevented without a mandatory lock.
+        let mmap = unsafe { memmap2::Mmap::map(file) }
+            .with_context(|| format!("mmap {}", pathname.display()))?;
+        if !is_settled()? {
+            info!(pathname = %pathname.display(), "changed while mapping, not mapped");
+            return Ok(None);
+        }
+
+        #[cfg(unix)]
+        if let Err(e) = mmap.advise(memmap2::Advice::Sequential) {
+            warn!(?e, "madvise");
+        }
+
+        Ok(Some(Self {
+            data: Bytes::from_owner(mmap),
+            pos: 0,
+        }))
+    }
+}
+
+impl ChunkReader for MmapReader {
+    fn read_chunk(&mut self, max: usize) -> std::io::Result<Bytes> {
+        let end = self.data.len().min(self.pos + max);
+        let chunk = self.data.slice(self.pos..end);
+        self.pos = end;
+        Ok(chunk)
+    }
+}
+
+#[cfg(target_os = "linux")]
+struct DirectReader {
+    file: File,
+    /// `O_DIRECT` failed so the pages are dropped after every read instead.
+    fadvise: bool,
+    /// Block-aligned buffers for `O_DIRECT`.
+    pool: Arc<BufferPool>,
+    pos: u64,
+}
+
+#[cfg(target_os = "linux")]
+impl DirectReader {
+    /// Covers the logical block size of common devices.
+    const ALIGNMENT: usize = 4096;
+
+    fn open(pathname: &Path) -> Fallible<Self> {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let (file, fadvise) = match std::fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_DIRECT)
+            .open(pathname)
+        {
+            Ok(file) => (file, false),
+            Err(e) => {
+                // e.g. tmpfs does not support O_DIRECT.
+                info!(?e, "O_DIRECT");
+                (
+                    File::open(pathname).with_context(|| format!("open {}", pathname.display()))?,
+                    true,
+                )
+            }
+        };
+
+        Ok(Self {
+            file,
+            fadvise,
+            pool: BufferPool::new(CHUNK_SIZE as usize, Self::ALIGNMENT),
+            pos: 0,
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl ChunkReader for DirectReader {
+    fn read_chunk(&mut self, max: usize) -> std::io::Result<Bytes> {
+        use std::os::fd::AsRawFd;
+
+        // O_DIRECT requires the length to be aligned as well. it stops at EOF anyway.
+        let mut buf = self.pool.take();
+        let len = max.next_multiple_of(Self::ALIGNMENT);
+        let read = fill(&mut self.file, &mut buf.as_mut_slice()[..len])?.min(max);
+
+        if self.fadvise {
+            let ret = unsafe {
+                libc::posix_fadvise(
+                    self.file.as_raw_fd(),
+                    self.pos as libc::off_t,
+                    read as libc::off_t,
+                    libc::POSIX_FADV_DONTNEED,
+                )
+            };
+            if ret != 0 {
+                warn!(ret, "posix_fadvise");
+            }
+        }
+        self.pos += read as u64;
+
+        Ok(buf.freeze(read))
+    }
+}
+
+/// Reads until `buf` is full or EOF, returning the length read.
+fn fill(reader: &mut impl Read, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(len) => filled += len,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
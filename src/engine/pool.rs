@@ -0,0 +1,184 @@
+use crate::prelude::*;
+use bytes::Bytes;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Buffers allocated by every pool, which the tests compare with the chunks read.
+#[cfg(test)]
+static ALLOCATED: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+/// Recycles zero-initialized chunk buffers. A buffer goes back to the pool when the last [`Bytes`]
+/// sharing it is dropped by the hashers, so the number of buffers stays at the number of chunks in
+/// flight once the queues are full.
+#[derive(Debug)]
+pub(super) struct BufferPool {
+    size: usize,
+    alignment: usize,
+    free: Mutex<Vec<Vec<u8>>>,
+}
+
+impl BufferPool {
+    /// Buffers hold `size` bytes starting at a multiple of `alignment`.
+    pub(super) fn new(size: usize, alignment: usize) -> Arc<Self> {
+        Arc::new(Self {
+            size,
+            alignment,
+            free: Mutex::new(vec![]),
+        })
+    }
+
+    pub(super) fn take(self: &Arc<Self>) -> PooledBuffer {
+        let data = self
+            .free
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pop()
+            .unwrap_or_else(|| {
+                debug!(size = self.size, "allocate");
+                #[cfg(test)]
+                ALLOCATED.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                vec![0; self.size + self.alignment - 1]
+            });
+        let offset = data.as_ptr().align_offset(self.alignment);
+        PooledBuffer {
+            data,
+            offset,
+            len: self.size,
+            pool: self.clone(),
+        }
+    }
+}
+
+pub(super) struct PooledBuffer {
+    data: Vec<u8>,
+    offset: usize,
+    len: usize,
+    pool: Arc<BufferPool>,
+}
+
+impl PooledBuffer {
+    pub(super) fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.data[self.offset..self.offset + self.len]
+    }
+
+    /// Shares the first `len` bytes without copying.
+    pub(super) fn freeze(mut self, len: usize) -> Bytes {
+        self.len = self.len.min(len);
+        Bytes::from_owner(self)
+    }
+}
+
+impl AsRef<[u8]> for PooledBuffer {
+    fn as_ref(&self) -> &[u8] {
+        &self.data[self.offset..self.offset + self.len]
+    }
+}
+
+impl Drop for PooledBuffer {
+    fn drop(&mut self) {
+        let data = std::mem::take(&mut self.data);
+        self.pool
+            .free
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::Registry;
+    use crate::engine::{self, CHUNK_QUEUE_SIZE, CHUNK_SIZE, HashOutcome, ReadBackend};
+    use std::io::Write;
+    use std::ops::ControlFlow;
+    use std::sync::atomic::Ordering;
+
+    /// Serializes the tests since [`ALLOCATED`] is shared.
+    static LOCK: Mutex<()> = Mutex::new(());
+
+    fn lock() -> std::sync::MutexGuard<'static, ()> {
+        LOCK.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    #[test]
+    fn reuses_dropped_buffer() {
+        let _lock = lock();
+        let pool = BufferPool::new(16, 1);
+
+        let buf = pool.take();
+        let ptr = buf.data.as_ptr();
+        drop(buf);
+        assert_eq!(pool.free.lock().unwrap().len(), 1);
+
+        let buf = pool.take();
+        assert_eq!(buf.data.as_ptr(), ptr);
+        assert!(pool.free.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn reuses_buffer_after_last_bytes_dropped() {
+        let _lock = lock();
+        let pool = BufferPool::new(16, 1);
+
+        let mut buf = pool.take();
+        buf.as_mut_slice()[..3].copy_from_slice(b"abc");
+        let bytes = buf.freeze(3);
+        let shared = bytes.clone();
+        assert_eq!(&shared[..], b"abc");
+
+        drop(bytes);
+        assert!(pool.free.lock().unwrap().is_empty());
+        drop(shared);
+        assert_eq!(pool.free.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn aligns_buffer() {
+        let _lock = lock();
+        let pool = BufferPool::new(4096, 4096);
+        for _ in 0..4 {
+            let mut buf = pool.take();
+            let data = buf.as_mut_slice();
+            assert_eq!(data.len(), 4096);
+            assert_eq!(data.as_ptr().align_offset(4096), 0);
+        }
+    }
+
+    /// The reader allocates no more buffers than the chunks in flight, however long the file is.
+    #[test]
+    fn bounded_allocations_in_steady_state() {
+        let _lock = lock();
+        let algorithms = [Registry::default().find("XXH3").unwrap()];
+        // the reader and hasher queues, one chunk per hasher, the one being fanned out and the one
+        // being read.
+        let in_flight = CHUNK_QUEUE_SIZE * (algorithms.len() + 1) + algorithms.len() + 2;
+        let chunks = in_flight * 3;
+
+        let pathname = std::env::temp_dir().join(format!("hash-gui-pool-{}", std::process::id()));
+        let mut file = std::fs::File::create(&pathname).unwrap();
+        let chunk = vec![0x5a; CHUNK_SIZE as usize];
+        for _ in 0..chunks {
+            file.write_all(&chunk).unwrap();
+        }
+        drop(file);
+
+        for backend in [ReadBackend::Buffered, ReadBackend::Direct] {
+            let allocated = ALLOCATED.load(Ordering::Relaxed);
+            let outcome = engine::hash_file(&pathname, &algorithms, backend, |_| {
+                ControlFlow::Continue(())
+            });
+            let allocated = ALLOCATED.load(Ordering::Relaxed) - allocated;
+
+            assert!(
+                matches!(outcome, Ok(HashOutcome::Finished(_))),
+                "{backend}: {outcome:?}"
+            );
+            assert!(
+                allocated <= in_flight,
+                "{backend}: {allocated} buffers for {chunks} chunks"
+            );
+        }
+
+        std::fs::remove_file(&pathname).unwrap();
+    }
+}
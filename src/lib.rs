@@ -1 +1,6 @@
+pub mod algorithm;
+pub mod checksum;
+pub mod dirhash;
+pub mod engine;
 pub mod prelude;
+pub mod walk;
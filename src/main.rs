@@ -1,20 +1,33 @@
 #![windows_subsystem = "windows"]
 
-use bytes::BytesMut;
+use base64::Engine;
+use hash_gui::algorithm::{self, Algorithm, Category, Params, Registry};
+use hash_gui::checksum::{self, ChecksumLine};
+use hash_gui::dirhash::{self, DirectoryFile};
+use hash_gui::engine::{
+    self, HashOutcome, HashResult, HashValue, JobControl, JobState, Progress, ReadBackend,
+};
 use hash_gui::prelude::*;
+use hash_gui::walk::{self, WalkOptions};
 use iced::futures::{SinkExt, Stream};
 use iced::widget::{
-    Space, column, container, horizontal_rule, progress_bar, row, scrollable, text, text_input,
+    Space, button, checkbox, column, container, horizontal_rule, pick_list, progress_bar, row,
+    scrollable, text, text_input,
 };
 use iced::window::settings::PlatformSpecific;
 use iced::{
     Alignment, Background, Border, Element, Length, Settings, Size, Subscription, Task, Theme,
-    keyboard, window,
+    keyboard, padding, window,
 };
-use sha2::{Digest, Sha256};
-use std::io::BufReader;
-use std::io::prelude::*;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use taskbar::Taskbar;
+
+mod taskbar;
 
 fn main() -> iced::Result {
     tracing_subscriber::fmt::init();
@@ -46,368 +59,2400 @@ fn main() -> iced::Result {
 
 #[derive(Debug, Clone)]
 enum Message {
-    CalculateProgress(Result<FileEntry, ()>),
+    CalculateProgress(FileEntry),
+    /// Refreshes elapsed times and ETAs even if no progress arrives.
+    Tick,
     FileDropped(PathBuf),
+    DirectoryWalked(Result<Vec<PathBuf>, String>),
+    DirectoryListed(PathBuf, WalkOptions, Result<Vec<DirectoryFile>, String>),
+    DirectoryDigestToggled(bool),
+    DirectoryExpanded(PathBuf, bool),
+    VerifyChecksumsToggled(bool),
+    ChecksumParsed(PathBuf, Result<Option<checksum::ChecksumFile>, String>),
+    ChecksumExpanded(PathBuf, bool),
     ClearHistory,
+    Retry(PathBuf),
+    Pause(PathBuf),
+    Resume(PathBuf),
+    Cancel(PathBuf),
+    TogglePauseAll,
+    CancelAll,
+    /// Moves the selection by the offset within the active entries.
+    MoveSelection(isize),
+    TogglePauseSelected,
+    CancelSelected,
+    RehashModifiedToggled(bool),
+    MaxJobsSelected(usize),
+    PerDeviceToggled(bool),
+    ReadBackendSelected(ReadBackend),
+    WindowOpened(window::Id),
+    /// The `HWND` of the window on Windows.
+    WindowHandleReceived(Option<isize>),
+    WalkFollowSymlinksToggled(bool),
+    WalkHiddenToggled(bool),
+    WalkOptionChanged(WalkField, String),
+    AlgorithmToggled(String, bool),
+    ParamChanged(String, ParamField, String),
+    KeyChanged(String, Secret),
+    KeyEncodingSelected(String, KeyEncoding),
 }
 
-#[derive(Default)]
-struct App {
-    file_entries: Vec<FileEntry>,
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum ParamField {
+    OutputSize,
+    Salt,
+    Personalization,
 }
 
-impl App {
-    fn title(&self) -> String {
-        let progress = self
-            .file_entries
-            .iter()
-            .fold(0f32, |progress_min, data| match data.state {
-                FileEntryState::Idle => progress_min,
-                FileEntryState::Calculating { progress } => {
-                    if progress_min == 0f32 {
-                        progress
-                    } else {
-                        progress_min.min(progress)
-                    }
-                }
-                FileEntryState::Finished { .. } => progress_min,
-            });
-        match progress {
-            0f32 => "Hash GUI".into(),
-            _ => format!("{progress:.0}% - Hash GUI"),
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum WalkField {
+    MaxDepth,
+    Include,
+    Exclude,
+}
+
+/// Raw user input of [`WalkOptions`] for dropped directories.
+#[derive(Debug, Default)]
+struct WalkInputs {
+    follow_symlinks: bool,
+    include_hidden: bool,
+    max_depth: String,
+    /// Whitespace separated since globs may contain commas.
+    include: String,
+    exclude: String,
+}
+
+impl WalkInputs {
+    fn get(&self, field: WalkField) -> &str {
+        match field {
+            WalkField::MaxDepth => &self.max_depth,
+            WalkField::Include => &self.include,
+            WalkField::Exclude => &self.exclude,
         }
     }
 
-    fn update(&mut self, message: Message) -> Task<Message> {
-        match message {
-            Message::CalculateProgress(data) => match data {
-                Ok(result) => self
-                    .file_entries
-                    .iter_mut()
-                    .find(|data| data.pathname == result.pathname)
-                    .map(|data| {
-                        data.state = result.state;
-                        Task::none()
-                    })
-                    .unwrap_or_else(Task::none),
-                Err(_e) => Task::none(),
-            },
-            Message::FileDropped(pathname) => {
-                info!(file_entries = ?self.file_entries);
-                if self
-                    .file_entries
-                    .iter()
-                    .all(|data| data.pathname != pathname)
-                    && pathname.is_file()
-                {
-                    self.file_entries.push(FileEntry {
-                        pathname,
-                        state: FileEntryState::Idle,
-                    });
-                }
-                Task::none()
-            }
-            Message::ClearHistory => {
-                if self.file_entries.is_empty() {
-                    iced::exit()
-                } else {
-                    self.file_entries.clear();
-                    Task::none()
-                }
-            }
+    fn get_mut(&mut self, field: WalkField) -> &mut String {
+        match field {
+            WalkField::MaxDepth => &mut self.max_depth,
+            WalkField::Include => &mut self.include,
+            WalkField::Exclude => &mut self.exclude,
         }
     }
 
-    fn subscription(&self) -> Subscription<Message> {
-        let mut subscriptions = self
-            .file_entries
-            .iter()
-            .filter(|data| match data.state {
-                FileEntryState::Idle | FileEntryState::Calculating { .. } => true,
-                FileEntryState::Finished { .. } => false,
-            })
-            .map(|data| {
-                Subscription::run_with_id(data.pathname.clone(), App::hash(data.clone()))
-                    .map(Message::CalculateProgress)
-            })
-            .collect::<Vec<_>>();
-
-        subscriptions.push(iced::event::listen_with(|event, _status, _id| {
-            if let iced::Event::Window(window::Event::FileDropped(path)) = event {
-                Some(Message::FileDropped(path))
-            } else {
-                None
-            }
-        }));
+    fn parse(&self) -> Fallible<WalkOptions> {
+        let options = WalkOptions {
+            follow_symlinks: self.follow_symlinks,
+            include_hidden: self.include_hidden,
+            max_depth: match self.max_depth.trim() {
+                "" => None,
+                data => Some(data.parse().context("max depth")?),
+            },
+            include: self.include.split_whitespace().map(Into::into).collect(),
+            exclude: self.exclude.split_whitespace().map(Into::into).collect(),
+            strict: false,
+        };
+        // reports invalid globs while typing rather than on drop.
+        walk::glob_set(&options.include).context("include")?;
+        walk::glob_set(&options.exclude).context("exclude")?;
+        Ok(options)
+    }
+}
 
-        subscriptions.push(iced::event::listen_with(|event, _status, _id| {
-            if let iced::Event::Keyboard(keyboard::Event::KeyReleased {
-                key: keyboard::Key::Named(keyboard::key::Named::Escape),
-                ..
-            }) = event
-            {
-                Some(Message::ClearHistory)
-            } else {
-                None
-            }
-        }));
+/// Key text typed by the user. `Debug` is redacted so that the key never reaches the logs.
+#[derive(Clone, Default)]
+struct Secret(String);
 
-        Subscription::batch(subscriptions)
+impl Debug for Secret {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<{} chars>", self.0.chars().count())
     }
+}
 
-    fn selectable_text_style(theme: &Theme, _status: text_input::Status) -> text_input::Style {
-        let palette = theme.extended_palette();
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+enum KeyEncoding {
+    #[default]
+    Hex,
+    Base64,
+    Utf8,
+}
 
-        // tweaks the default active style.
-        text_input::Style {
-            background: Background::Color(palette.background.base.color),
-            border: Border::default(),
-            icon: palette.background.weak.text,
-            placeholder: palette.background.strong.color,
-            value: palette.background.base.text,
-            selection: palette.primary.weak.color,
-        }
+impl KeyEncoding {
+    const ALL: [KeyEncoding; 3] = [KeyEncoding::Hex, KeyEncoding::Base64, KeyEncoding::Utf8];
+
+    /// The error carries no part of `data` since the decoders quote the offending character,
+    /// which would leak the key to the logs.
+    fn decode(self, data: &str) -> Fallible<Vec<u8>> {
+        let ret = match self {
+            KeyEncoding::Hex => hex::decode(data.trim()).ok(),
+            KeyEncoding::Base64 => base64::engine::general_purpose::STANDARD
+                .decode(data.trim())
+                .ok(),
+            // whitespace may be a part of a passphrase.
+            KeyEncoding::Utf8 => Some(data.as_bytes().to_vec()),
+        };
+        ret.ok_or_else(|| anyhow!("key is not valid {self}"))
     }
+}
 
-    fn selectable_text_result_style(
-        &self,
-        index: usize,
-        theme: &Theme,
-        _status: text_input::Status,
-    ) -> text_input::Style {
-        let palette = theme.extended_palette();
+impl Display for KeyEncoding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            KeyEncoding::Hex => "hex",
+            KeyEncoding::Base64 => "base64",
+            KeyEncoding::Utf8 => "UTF-8",
+        })
+    }
+}
 
-        let background = match self.file_entries.first() {
-            Some(FileEntry {
-                state: FileEntryState::Finished { hash },
-                ..
-            }) => match self.file_entries.get(index) {
-                None
-                | Some(FileEntry {
-                    state: FileEntryState::Idle,
-                    ..
-                })
-                | Some(FileEntry {
-                    state: FileEntryState::Calculating { .. },
-                    ..
-                }) => Background::Color(palette.background.base.color),
-                Some(FileEntry {
-                    state: FileEntryState::Finished { hash: other_hash },
-                    ..
-                }) if hash == other_hash => Background::Color(palette.success.base.color),
-                Some(_) => Background::Color(palette.danger.base.color),
-            },
-            _ => Background::Color(palette.background.base.color),
-        };
+/// Raw user input of [`Params`] so that half-typed values survive re-rendering.
+#[derive(Debug, Default)]
+struct ParamInputs {
+    output_size: String,
+    key: Secret,
+    key_encoding: KeyEncoding,
+    salt: String,
+    personalization: String,
+}
 
-        // tweaks the default active style.
-        text_input::Style {
-            background,
-            border: Border::default(),
-            icon: palette.background.weak.text,
-            placeholder: palette.background.strong.color,
-            value: palette.background.base.text,
-            selection: palette.primary.weak.color,
+impl ParamInputs {
+    fn get(&self, field: ParamField) -> &str {
+        match field {
+            ParamField::OutputSize => &self.output_size,
+            ParamField::Salt => &self.salt,
+            ParamField::Personalization => &self.personalization,
         }
     }
 
-    fn view(&self) -> Element<'_, Message> {
-        if self.file_entries.is_empty() {
-            return container(column([
-                row([
-                    text("Calculate").into(),
-                    Space::with_width(4).into(),
-                    text("Drop files here")
-                        .color(self.theme().extended_palette().primary.strong.color)
-                        .into(),
-                ])
-                .into(),
-                row([
-                    text("Clear/Exit").into(),
-                    Space::with_width(4).into(),
-                    text(if cfg!(target_os = "macos") {
-                        "⎋"
-                    } else {
-                        "Esc"
-                    })
-                    .color(self.theme().extended_palette().primary.strong.color)
-                    .into(),
-                ])
-                .into(),
-            ]))
-            .center(Length::Fill)
-            .into();
+    fn get_mut(&mut self, field: ParamField) -> &mut String {
+        match field {
+            ParamField::OutputSize => &mut self.output_size,
+            ParamField::Salt => &mut self.salt,
+            ParamField::Personalization => &mut self.personalization,
         }
+    }
 
-        let mut children = vec![];
-        for (i, data) in self.file_entries.iter().enumerate() {
-            if 0 < i {
-                children.push(horizontal_rule(8).into());
-            }
+    fn parse(&self) -> Fallible<Params> {
+        Ok(Params {
+            output_size: match self.output_size.trim() {
+                "" => None,
+                data => Some(data.parse().context("output size")?),
+            },
+            key: self.key_encoding.decode(&self.key.0)?,
+            salt: hex::decode(self.salt.trim()).context("salt")?,
+            personalization: hex::decode(self.personalization.trim()).context("personalization")?,
+        })
+    }
+}
 
-            children.push(
-                row([
-                    text("pathname: ").into(),
-                    text_input("", &data.pathname.display().to_string())
-                        .size(12)
-                        .style(Self::selectable_text_style)
-                        .into(),
-                ])
-                .into(),
-            );
+struct App {
+    file_entries: Vec<FileEntry>,
+    /// Directories dropped in the directory digest mode. The files are in `file_entries` too.
+    directories: Vec<DirectoryEntry>,
+    /// Drops directories as a single [`dirhash`] digest instead of one entry per file.
+    directory_digest: bool,
+    /// Checksum files being verified. The listed files are in `file_entries` too.
+    checksum_files: Vec<ChecksumEntry>,
+    /// Verifies dropped [`checksum`] files instead of hashing them.
+    verify_checksums: bool,
+    /// Dropped files waiting to be told apart from checksum files, parsed one at a time in the
+    /// dropped order.
+    dropped_files: VecDeque<PathBuf>,
+    /// Why dropped files that look like checksum files were hashed instead, shown on their entries.
+    checksum_errors: HashMap<PathBuf, String>,
+    registry: Registry,
+    /// Algorithms applied to newly dropped files.
+    algorithms: Vec<Arc<dyn Algorithm>>,
+    /// User input of the algorithm parameters per algorithm name.
+    param_inputs: HashMap<String, ParamInputs>,
+    /// Re-hashes files that changed while being hashed, up to [`App::MAX_REHASHES`] times.
+    rehash_modified: bool,
+    /// Files hashed at the same time. Paused jobs do not count.
+    max_jobs: usize,
+    /// Hashes at most one file at a time per device so that disks do not seek between files.
+    per_device: bool,
+    /// Read backend applied to newly dropped files.
+    read_backend: ReadBackend,
+    taskbar: Taskbar,
+    /// Active entry targeted by the per-row keyboard shortcuts.
+    selected: Option<PathBuf>,
+    walk_inputs: WalkInputs,
+    /// Error of the last directory walk, shown next to the walk options.
+    walk_error: Option<String>,
+}
 
-            children.push(
-                row([
-                    text("SHA256: ").into(),
-                    match data.state {
-                        FileEntryState::Idle => progress_bar(0.0..=100.0, 0.0).height(16).into(),
-                        FileEntryState::Calculating { progress } => {
-                            progress_bar(0.0..=100.0, progress).height(16).into()
-                        }
-                        FileEntryState::Finished { .. } => text_input(
-                            "",
-                            match &data.state {
-                                FileEntryState::Finished { hash } => hash,
-                                FileEntryState::Idle | FileEntryState::Calculating { .. } => "",
-                            },
-                        )
-                        .size(12)
-                        .style(move |theme, status| {
-                            if i == 0 {
-                                Self::selectable_text_style(theme, status)
-                            } else {
-                                self.selectable_text_result_style(i, theme, status)
-                            }
-                        })
-                        .into(),
-                    },
-                ])
-                .align_y(Alignment::Center)
-                .into(),
-            );
+impl Default for App {
+    fn default() -> Self {
+        let registry = Registry::default();
+        let algorithms = vec![
+            registry
+                .find("SHA256")
+                .expect("SHA256 is registered by default"),
+        ];
+        Self {
+            file_entries: vec![],
+            directories: vec![],
+            directory_digest: false,
+            checksum_files: vec![],
+            verify_checksums: true,
+            dropped_files: VecDeque::new(),
+            checksum_errors: HashMap::new(),
+            registry,
+            algorithms,
+            param_inputs: HashMap::new(),
+            rehash_modified: false,
+            max_jobs: 2,
+            per_device: false,
+            read_backend: ReadBackend::default(),
+            taskbar: Taskbar::default(),
+            selected: None,
+            walk_inputs: WalkInputs::default(),
+            walk_error: None,
         }
-        scrollable(column(children)).into()
     }
+}
 
-    fn theme(&self) -> Theme {
-        Theme::default()
-    }
+impl App {
+    const MAX_REHASHES: usize = 3;
+    const MAX_JOBS_OPTIONS: [usize; 6] = [1, 2, 3, 4, 8, 16];
 
-    fn hash(entry: FileEntry) -> impl Stream<Item = Result<FileEntry, ()>> {
-        iced::stream::try_channel(3, async move |mut output| {
-            let mut reader = BufReader::with_capacity(
-                8 * 1024 * 1024,
-                match std::fs::File::open(&entry.pathname) {
-                    Ok(data) => data,
-                    Err(e) => {
-                        warn!(?e);
-                        return Err(());
-                    }
-                },
-            );
-            let filesize = match std::fs::symlink_metadata(&entry.pathname) {
-                Ok(data) => data.len(),
-                Err(e) => {
-                    warn!(?e, "metadata");
-                    return Err(());
-                }
-            };
+    fn title(&self) -> String {
+        match self.overall_progress() {
+            Some(progress) => format!(
+                "{:.0}% · {}/{} files - Hash GUI",
+                progress.percent(),
+                progress.done,
+                progress.files,
+            ),
+            None => "Hash GUI".into(),
+        }
+    }
 
-            let (tx, rx) = std::sync::mpsc::sync_channel(10);
+    /// Progress of all but cancelled entries weighted by the file size, or `None` if nothing is
+    /// queued or running. Entries of unknown size only count as files.
+    fn overall_progress(&self) -> Option<OverallProgress> {
+        if !self.file_entries.iter().any(|data| data.is_active()) {
+            return None;
+        }
 
-            if output
-                .send(FileEntry {
-                    pathname: entry.pathname.clone(),
-                    state: FileEntryState::Calculating { progress: 0.0 },
-                })
-                .await
-                .is_err()
-            {
-                info!("disconnected");
-                return Err(());
+        let mut ret = OverallProgress::default();
+        for data in &self.file_entries {
+            let size = match data.state {
+                FileEntryState::Calculating {
+                    progress:
+                        Progress {
+                            total: Some(total), ..
+                        },
+                } => total,
+                _ => data.size.unwrap_or_default(),
+            };
+            match data.state {
+                FileEntryState::Waiting | FileEntryState::Idle => {
+                    ret.total += size;
+                }
+                FileEntryState::Calculating { progress } => {
+                    ret.processed += progress.processed.min(size);
+                    ret.total += size;
+                }
+                FileEntryState::Finished { .. } => {
+                    ret.processed += size;
+                    ret.total += size;
+                    ret.done += 1;
+                }
+                // nothing is left to read.
+                FileEntryState::Failed { .. } => {
+                    ret.done += 1;
+                }
+                FileEntryState::Cancelled => continue,
             }
+            ret.files += 1;
+        }
+        Some(ret)
+    }
 
-            let read_span = info_span!("read", pathname = entry.pathname.display().to_string());
-
-            tokio::task::spawn_blocking(move || {
-                let _guard = read_span.enter();
-
-                let mut remain = filesize;
+    fn update(&mut self, message: Message) -> Task<Message> {
+        let task = self.handle_message(message);
+        self.refresh_checksums();
+        self.schedule();
+        self.refresh_directories();
+        let percent = self.overall_progress().map(|data| data.percent());
+        self.taskbar.set_progress(percent);
+        task
+    }
 
-                while 0 < remain {
-                    let read_size = (1024 * 1024).min(remain) as usize;
-                    let mut buf = BytesMut::with_capacity(read_size);
-                    unsafe {
-                        buf.set_len(read_size);
+    fn handle_message(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::CalculateProgress(result) => self
+                .file_entries
+                .iter_mut()
+                // drops updates from a cancelled or restarted job.
+                .find(|data| {
+                    data.pathname == result.pathname
+                        && Arc::ptr_eq(&data.control, &result.control)
+                        && data.control.state() != JobState::Cancelled
+                })
+                .map(|data| {
+                    data.state = result.state;
+                    let now = Instant::now();
+                    match &data.state {
+                        FileEntryState::Calculating { progress } => {
+                            data.throughput.update(now, progress.processed)
+                        }
+                        FileEntryState::Finished { .. } | FileEntryState::Failed { .. } => {
+                            data.throughput.finished = Some(now)
+                        }
+                        FileEntryState::Waiting
+                        | FileEntryState::Idle
+                        | FileEntryState::Cancelled => {}
                     }
-                    if let Err(e) = reader.read_exact(&mut buf) {
-                        warn!(?e, "read");
-                        return;
+                    if let FileEntryState::Finished { modified: true, .. } = data.state
+                        && self.rehash_modified
+                        && data.rehashes < Self::MAX_REHASHES
+                    {
+                        info!(pathname = ?data.pathname, "rehash");
+                        data.rehashes += 1;
+                        data.restart();
                     }
-
-                    remain -= buf.len() as u64;
-                    if let Err(e) = tx.send(buf.freeze()) {
-                        info!(?e, "disconnected");
-                        return;
+                    Task::none()
+                })
+                .unwrap_or_else(Task::none),
+            Message::Tick => Task::none(),
+            Message::WindowOpened(id) => {
+                window::run_with_handle(id, taskbar::hwnd).map(Message::WindowHandleReceived)
+            }
+            Message::WindowHandleReceived(hwnd) => {
+                if let Some(hwnd) = hwnd {
+                    self.taskbar.attach(hwnd);
+                }
+                Task::none()
+            }
+            Message::FileDropped(pathname) => {
+                info!(file_entries = ?self.file_entries);
+                if !pathname.is_dir() {
+                    if !self.verify_checksums {
+                        self.add_file(pathname, self.algorithms.clone());
+                        return Task::none();
                     }
+                    self.dropped_files.push_back(pathname);
+                    return if self.dropped_files.len() == 1 {
+                        self.parse_dropped_file()
+                    } else {
+                        Task::none()
+                    };
                 }
 
-                info!("finish");
-            });
-
-            let hash_span = info_span!("read", pathname = entry.pathname.display().to_string());
-
-            tokio::task::spawn_blocking(move || {
-                let _guard = hash_span.enter();
-
-                let mut hasher = Sha256::new();
-                let mut sum = 0u64;
-
-                for data in rx {
-                    Digest::update(&mut hasher, &data);
-
-                    sum += data.len() as u64;
-
-                    match output.try_send(FileEntry {
-                        pathname: entry.pathname.clone(),
-                        state: FileEntryState::Calculating {
-                            progress: ((sum as f64) / (filesize as f64) * 100.0) as f32,
+                match self.walk_inputs.parse() {
+                    Ok(options) if self.directory_digest => {
+                        let root = pathname.clone();
+                        let listed = options.clone();
+                        Task::perform(
+                            async move {
+                                tokio::task::spawn_blocking(move || {
+                                    dirhash::list(&pathname, &options).map_err(|e| format!("{e:#}"))
+                                })
+                                .await
+                                .map_err(|e| e.to_string())?
+                            },
+                            move |data| {
+                                Message::DirectoryListed(root.clone(), listed.clone(), data)
+                            },
+                        )
+                    }
+                    Ok(options) => Task::perform(
+                        async move {
+                            tokio::task::spawn_blocking(move || {
+                                walk::walk(&pathname, &options).map_err(|e| format!("{e:#}"))
+                            })
+                            .await
+                            .map_err(|e| e.to_string())?
                         },
-                    }) {
-                        Err(e) if e.is_disconnected() => {
-                            info!("disconnected");
-                            return;
+                        Message::DirectoryWalked,
+                    ),
+                    Err(e) => {
+                        warn!(?e, "walk options");
+                        Task::none()
+                    }
+                }
+            }
+            Message::DirectoryWalked(data) => {
+                match data {
+                    Ok(pathnames) => {
+                        self.walk_error = None;
+                        for pathname in pathnames {
+                            self.add_file(pathname, self.algorithms.clone());
+                        }
+                    }
+                    Err(e) => {
+                        warn!(e, "walk");
+                        self.walk_error = Some(e);
+                    }
+                }
+                Task::none()
+            }
+            Message::DirectoryListed(root, options, data) => {
+                match data {
+                    Ok(files) => {
+                        self.walk_error = None;
+                        if self.directories.iter().all(|data| data.root != root) {
+                            for file in &files {
+                                self.add_file(file.pathname.clone(), self.algorithms.clone());
+                            }
+                            self.directories.push(DirectoryEntry {
+                                root,
+                                options,
+                                files,
+                                algorithms: self.algorithms.clone(),
+                                results: None,
+                                expanded: false,
+                            });
                         }
-                        Ok(_) | Err(_) => {}
+                    }
+                    Err(e) => {
+                        warn!(e, "list");
+                        self.walk_error = Some(e);
                     }
                 }
+                Task::none()
+            }
+            Message::DirectoryDigestToggled(checked) => {
+                self.directory_digest = checked;
+                Task::none()
+            }
+            Message::DirectoryExpanded(root, expanded) => {
+                if let Some(data) = self.directories.iter_mut().find(|data| data.root == root) {
+                    data.expanded = expanded;
+                }
+                Task::none()
+            }
+            Message::ChecksumParsed(pathname, data) => {
+                // ignores a parse started before clearing the history.
+                if self.dropped_files.front() != Some(&pathname) {
+                    return Task::none();
+                }
+                self.dropped_files.pop_front();
 
-                output
-                    .try_send(FileEntry {
-                        pathname: entry.pathname.clone(),
-                        state: FileEntryState::Finished {
-                            hash: format!("{:x}", hasher.finalize()),
-                        },
-                    })
-                    .ok();
-
-                info!("finish");
-            });
-
-            Ok(())
-        })
-    }
-}
-
-#[derive(Debug, Clone)]
-struct FileEntry {
-    pathname: PathBuf,
-    state: FileEntryState,
-}
-
-#[derive(Debug, Clone)]
-enum FileEntryState {
+                match data {
+                    Ok(Some(data)) => self.add_checksum_file(pathname, data),
+                    Ok(None) => self.add_file(pathname, self.algorithms.clone()),
+                    Err(e) => {
+                        // hashes the file itself instead.
+                        warn!(e, "checksum");
+                        self.add_file(pathname.clone(), self.algorithms.clone());
+                        self.checksum_errors.insert(pathname, e);
+                    }
+                }
+                self.parse_dropped_file()
+            }
+            Message::VerifyChecksumsToggled(checked) => {
+                self.verify_checksums = checked;
+                Task::none()
+            }
+            Message::ChecksumExpanded(pathname, expanded) => {
+                if let Some(data) = self
+                    .checksum_files
+                    .iter_mut()
+                    .find(|data| data.pathname == pathname)
+                {
+                    data.expanded = expanded;
+                }
+                Task::none()
+            }
+            Message::ClearHistory => {
+                if self.is_empty() {
+                    iced::exit()
+                } else {
+                    // unblocks paused jobs.
+                    for data in &self.file_entries {
+                        data.control.cancel();
+                    }
+                    self.file_entries.clear();
+                    self.directories.clear();
+                    self.checksum_files.clear();
+                    self.dropped_files.clear();
+                    self.checksum_errors.clear();
+                    self.selected = None;
+                    Task::none()
+                }
+            }
+            Message::Retry(pathname) => {
+                if let Some(data) = self.find_entry_mut(&pathname) {
+                    data.restart();
+                }
+                Task::none()
+            }
+            Message::Pause(pathname) => {
+                if let Some(data) = self.find_entry_mut(&pathname) {
+                    data.pause();
+                }
+                Task::none()
+            }
+            Message::Resume(pathname) => {
+                if let Some(data) = self.find_entry_mut(&pathname) {
+                    data.resume();
+                }
+                Task::none()
+            }
+            Message::Cancel(pathname) => {
+                if let Some(data) = self.find_entry_mut(&pathname) {
+                    data.control.cancel();
+                    data.state = FileEntryState::Cancelled;
+                }
+                Task::none()
+            }
+            Message::MoveSelection(offset) => {
+                let pathnames = self
+                    .visible_entries()
+                    .into_iter()
+                    .filter(|data| data.is_active())
+                    .map(|data| data.pathname.clone())
+                    .collect::<Vec<_>>();
+                let position = self
+                    .selected
+                    .as_ref()
+                    .and_then(|selected| pathnames.iter().position(|data| data == selected));
+                let index = match position {
+                    Some(index) => index.saturating_add_signed(offset),
+                    None if offset < 0 => pathnames.len().saturating_sub(1),
+                    None => 0,
+                };
+                self.selected = pathnames
+                    .get(index.min(pathnames.len().saturating_sub(1)))
+                    .cloned();
+                Task::none()
+            }
+            Message::TogglePauseSelected => {
+                if let Some(data) = self.selected_entry() {
+                    if data.is_running() {
+                        data.pause();
+                    } else {
+                        data.resume();
+                    }
+                }
+                Task::none()
+            }
+            Message::CancelSelected => {
+                if let Some(data) = self.selected_entry() {
+                    data.control.cancel();
+                    data.state = FileEntryState::Cancelled;
+                }
+                Task::none()
+            }
+            Message::TogglePauseAll => {
+                let running = self
+                    .file_entries
+                    .iter()
+                    .filter(|data| data.is_active())
+                    .any(FileEntry::is_running);
+                for data in self.file_entries.iter_mut().filter(|data| data.is_active()) {
+                    if running {
+                        data.pause();
+                    } else {
+                        data.resume();
+                    }
+                }
+                Task::none()
+            }
+            Message::CancelAll => {
+                for data in self.file_entries.iter_mut().filter(|data| data.is_active()) {
+                    data.control.cancel();
+                    data.state = FileEntryState::Cancelled;
+                }
+                Task::none()
+            }
+            Message::RehashModifiedToggled(checked) => {
+                self.rehash_modified = checked;
+                Task::none()
+            }
+            Message::MaxJobsSelected(max_jobs) => {
+                self.max_jobs = max_jobs;
+                Task::none()
+            }
+            Message::PerDeviceToggled(checked) => {
+                self.per_device = checked;
+                Task::none()
+            }
+            Message::ReadBackendSelected(backend) => {
+                self.read_backend = backend;
+                Task::none()
+            }
+            Message::WalkFollowSymlinksToggled(checked) => {
+                self.walk_inputs.follow_symlinks = checked;
+                Task::none()
+            }
+            Message::WalkHiddenToggled(checked) => {
+                self.walk_inputs.include_hidden = checked;
+                Task::none()
+            }
+            Message::WalkOptionChanged(field, value) => {
+                *self.walk_inputs.get_mut(field) = value;
+                Task::none()
+            }
+            Message::AlgorithmToggled(name, checked) => {
+                if checked {
+                    match self.configured_algorithm(&name) {
+                        Ok(algorithm) => {
+                            self.algorithms.push(algorithm);
+                            // keeps the registry order for the result rows.
+                            let registry = &self.registry;
+                            self.algorithms.sort_by_key(|algorithm| {
+                                registry
+                                    .iter()
+                                    .position(|data| data.name() == algorithm.name())
+                            });
+                        }
+                        Err(e) => warn!(?e, "configure"),
+                    }
+                } else if 1 < self.algorithms.len() {
+                    self.algorithms.retain(|data| data.name() != name);
+                }
+                Task::none()
+            }
+            Message::ParamChanged(name, field, value) => {
+                *self
+                    .param_inputs
+                    .entry(name.clone())
+                    .or_default()
+                    .get_mut(field) = value;
+                self.reconfigure(&name);
+                Task::none()
+            }
+            Message::KeyChanged(name, value) => {
+                self.param_inputs.entry(name.clone()).or_default().key = value;
+                self.reconfigure(&name);
+                Task::none()
+            }
+            Message::KeyEncodingSelected(name, encoding) => {
+                self.param_inputs
+                    .entry(name.clone())
+                    .or_default()
+                    .key_encoding = encoding;
+                self.reconfigure(&name);
+                Task::none()
+            }
+        }
+    }
+
+    /// Applies the current parameter input to the selected algorithm if it is valid.
+    fn reconfigure(&mut self, name: &str) {
+        if let Ok(algorithm) = self.configured_algorithm(name) {
+            for data in self.algorithms.iter_mut() {
+                if data.name() == name {
+                    *data = algorithm.clone();
+                }
+            }
+        }
+    }
+
+    /// File entries in the displayed order, skipping the files of collapsed directories and
+    /// checksum files.
+    fn visible_entries(&self) -> Vec<&FileEntry> {
+        let mut ret = vec![];
+        let mut members = HashSet::new();
+        for directory in &self.directories {
+            let pathnames = directory.files.iter().map(|file| file.pathname.as_path());
+            if directory.expanded {
+                let pathnames = pathnames.clone().collect::<HashSet<_>>();
+                ret.extend(
+                    self.file_entries
+                        .iter()
+                        .filter(|data| pathnames.contains(data.pathname.as_path())),
+                );
+            }
+            members.extend(pathnames);
+        }
+        for checksum_file in &self.checksum_files {
+            let pathnames = checksum_file
+                .lines
+                .iter()
+                .map(|line| line.pathname.as_path());
+            if checksum_file.expanded {
+                let pathnames = pathnames.clone().collect::<HashSet<_>>();
+                ret.extend(
+                    self.file_entries
+                        .iter()
+                        .filter(|data| pathnames.contains(data.pathname.as_path())),
+                );
+            }
+            members.extend(pathnames);
+        }
+        ret.extend(
+            self.file_entries
+                .iter()
+                .filter(|data| !members.contains(data.pathname.as_path())),
+        );
+        ret
+    }
+
+    /// Returns the selected entry while it is active.
+    fn selected_entry(&mut self) -> Option<&mut FileEntry> {
+        let selected = self.selected.clone()?;
+        self.find_entry_mut(&selected)
+            .filter(|data| data.is_active())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.file_entries.is_empty()
+            && self.directories.is_empty()
+            && self.checksum_files.is_empty()
+    }
+
+    fn add_file(&mut self, pathname: PathBuf, algorithms: Vec<Arc<dyn Algorithm>>) {
+        if self
+            .file_entries
+            .iter()
+            .any(|data| data.pathname == pathname)
+        {
+            return;
+        }
+
+        let target = match pathname.symlink_metadata() {
+            Ok(metadata) if metadata.is_symlink() => {
+                Some(std::fs::canonicalize(&pathname).unwrap_or_else(|e| {
+                    warn!(?e, "canonicalize");
+                    std::fs::read_link(&pathname).unwrap_or_default()
+                }))
+            }
+            _ => None,
+        };
+        let metadata = std::fs::metadata(&pathname).ok();
+        self.file_entries.push(FileEntry {
+            device: metadata.as_ref().and_then(device),
+            // streams are read until EOF.
+            size: metadata
+                .filter(|data| data.is_file() && 0 < data.len())
+                .map(|data| data.len()),
+            pathname,
+            target,
+            algorithms,
+            backend: self.read_backend,
+            state: FileEntryState::Waiting,
+            control: Default::default(),
+            throughput: Throughput::default(),
+            rehashes: 0,
+            generation: 0,
+            resume_requested: false,
+        });
+    }
+
+    /// Parses the first of [`App::dropped_files`] off the UI thread.
+    fn parse_dropped_file(&self) -> Task<Message> {
+        let Some(pathname) = self.dropped_files.front().cloned() else {
+            return Task::none();
+        };
+        let registry = self.registry.clone();
+        Task::perform(
+            {
+                let pathname = pathname.clone();
+                async move {
+                    tokio::task::spawn_blocking(move || {
+                        checksum::parse(&pathname, &registry).map_err(|e| format!("{e:#}"))
+                    })
+                    .await
+                    .map_err(|e| e.to_string())?
+                }
+            },
+            move |data| Message::ChecksumParsed(pathname.clone(), data),
+        )
+    }
+
+    /// Queues the listed files, including missing ones so that they fail like the others.
+    fn add_checksum_file(&mut self, pathname: PathBuf, data: checksum::ChecksumFile) {
+        if self
+            .checksum_files
+            .iter()
+            .any(|data| data.pathname == pathname)
+        {
+            return;
+        }
+
+        info!(
+            ?pathname,
+            algorithm = data.algorithm.name(),
+            lines = data.lines.len(),
+            "verify"
+        );
+        for line in &data.lines {
+            self.add_file(line.pathname.clone(), vec![data.algorithm.clone()]);
+        }
+        self.checksum_files.push(ChecksumEntry {
+            pathname,
+            algorithm: data.algorithm,
+            lines: data.lines,
+            improperly_formatted: data.improperly_formatted,
+            expanded: false,
+        });
+    }
+
+    /// Hashes listed files again with the algorithm of the checksum file when they were dropped
+    /// before with other algorithms.
+    fn refresh_checksums(&mut self) {
+        for checksum_file in &self.checksum_files {
+            let algorithm = &checksum_file.algorithm;
+            for line in &checksum_file.lines {
+                let Some(data) = self
+                    .file_entries
+                    .iter_mut()
+                    .find(|data| data.pathname == line.pathname)
+                else {
+                    continue;
+                };
+                let FileEntryState::Finished { results, .. } = &data.state else {
+                    continue;
+                };
+                if results.iter().any(|data| {
+                    algorithm::is_comparable(data.algorithm.as_ref(), algorithm.as_ref())
+                }) {
+                    continue;
+                }
+
+                info!(pathname = ?data.pathname, algorithm = algorithm.name(), "rehash");
+                data.algorithms.push(algorithm.clone());
+                data.restart();
+            }
+        }
+    }
+
+    fn checksum_status(&self, line: &ChecksumLine, algorithm: &dyn Algorithm) -> ChecksumStatus {
+        let Some(data) = self
+            .file_entries
+            .iter()
+            .find(|data| data.pathname == line.pathname)
+        else {
+            return ChecksumStatus::Pending;
+        };
+        match &data.state {
+            FileEntryState::Waiting | FileEntryState::Idle | FileEntryState::Calculating { .. } => {
+                ChecksumStatus::Pending
+            }
+            FileEntryState::Finished { results, .. } => {
+                match results
+                    .iter()
+                    .find(|data| algorithm::is_comparable(data.algorithm.as_ref(), algorithm))
+                {
+                    Some(data) if data.value == line.expected => ChecksumStatus::Ok,
+                    Some(_) => ChecksumStatus::Failed,
+                    // queued again by `refresh_checksums`.
+                    None => ChecksumStatus::Pending,
+                }
+            }
+            FileEntryState::Failed {
+                kind: std::io::ErrorKind::NotFound,
+                ..
+            } => ChecksumStatus::Missing,
+            FileEntryState::Failed { .. } => ChecksumStatus::Unreadable,
+            FileEntryState::Cancelled => ChecksumStatus::Cancelled,
+        }
+    }
+
+    /// Computes the digest of directories whose files all finished, and drops it once a file is
+    /// hashed again.
+    fn refresh_directories(&mut self) {
+        if self.directories.is_empty() {
+            return;
+        }
+
+        let entries = self
+            .file_entries
+            .iter()
+            .map(|data| (data.pathname.as_path(), data))
+            .collect::<HashMap<_, _>>();
+        for directory in &mut self.directories {
+            let results = directory
+                .files
+                .iter()
+                .map(|file| match entries.get(file.pathname.as_path()) {
+                    Some(FileEntry {
+                        state: FileEntryState::Finished { results, .. },
+                        ..
+                    }) => Some(results),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>();
+            let Some(results) = results else {
+                directory.results = None;
+                continue;
+            };
+            if directory.results.is_some() {
+                continue;
+            }
+
+            directory.results = Some(
+                directory
+                    .algorithms
+                    .iter()
+                    .map(|algorithm| {
+                        // a file dropped before may have been hashed with other algorithms.
+                        let values = results
+                            .iter()
+                            .map(|data| {
+                                data.iter()
+                                    .find(|data| {
+                                        algorithm::is_comparable(
+                                            data.algorithm.as_ref(),
+                                            algorithm.as_ref(),
+                                        )
+                                    })
+                                    .map(|data| &data.value)
+                            })
+                            .collect::<Option<Vec<_>>>()?;
+                        Some(HashResult {
+                            algorithm: algorithm.clone(),
+                            value: dirhash::digest(
+                                algorithm.as_ref(),
+                                directory.files.iter().zip(values),
+                            ),
+                        })
+                    })
+                    .collect(),
+            );
+        }
+    }
+
+    /// Starts waiting entries in the dropped order while there are free job slots.
+    fn schedule(&mut self) {
+        let running = self
+            .file_entries
+            .iter()
+            .filter(|data| data.is_started() && data.control.state() == JobState::Running)
+            .collect::<Vec<_>>();
+        let mut jobs = running.len();
+        let mut busy_devices = running
+            .iter()
+            .filter_map(|data| data.device)
+            .collect::<HashSet<_>>();
+
+        // resumes paused jobs first since they have read a part of the file already.
+        for data in &mut self.file_entries {
+            if self.max_jobs <= jobs {
+                break;
+            }
+            if !data.resume_requested || !data.is_started() {
+                continue;
+            }
+            if let Some(device) = data.device
+                && self.per_device
+                && !busy_devices.insert(device)
+            {
+                continue;
+            }
+
+            data.resume_requested = false;
+            data.control.resume();
+            jobs += 1;
+        }
+
+        for data in &mut self.file_entries {
+            if self.max_jobs <= jobs {
+                break;
+            }
+            if !matches!(data.state, FileEntryState::Waiting)
+                || data.control.state() != JobState::Running
+            {
+                continue;
+            }
+            if let Some(device) = data.device
+                && self.per_device
+                && !busy_devices.insert(device)
+            {
+                continue;
+            }
+
+            data.state = FileEntryState::Idle;
+            jobs += 1;
+        }
+    }
+
+    fn find_entry_mut(&mut self, pathname: &Path) -> Option<&mut FileEntry> {
+        self.file_entries
+            .iter_mut()
+            .find(|data| data.pathname == pathname)
+    }
+
+    /// Returns the registered algorithm with the parameters typed by the user applied.
+    fn configured_algorithm(&self, name: &str) -> Fallible<Arc<dyn Algorithm>> {
+        let params = match self.param_inputs.get(name) {
+            Some(data) => data.parse()?,
+            None => Params::default(),
+        };
+        self.registry.configure(name, &params)
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        let mut subscriptions = self
+            .file_entries
+            .iter()
+            .filter(|data| data.is_started())
+            .map(|data| {
+                // a new id restarts the stream, even within the update that finished the last one.
+                Subscription::run_with_id(
+                    (data.pathname.clone(), data.generation),
+                    App::hash(data.clone()),
+                )
+                .map(Message::CalculateProgress)
+            })
+            .collect::<Vec<_>>();
+
+        subscriptions.push(window::open_events().map(Message::WindowOpened));
+
+        subscriptions.push(iced::event::listen_with(|event, _status, _id| {
+            if let iced::Event::Window(window::Event::FileDropped(path)) = event {
+                Some(Message::FileDropped(path))
+            } else {
+                None
+            }
+        }));
+
+        subscriptions.push(iced::event::listen_with(|event, _status, _id| {
+            if let iced::Event::Keyboard(keyboard::Event::KeyReleased {
+                key: keyboard::Key::Named(keyboard::key::Named::Escape),
+                ..
+            }) = event
+            {
+                Some(Message::ClearHistory)
+            } else {
+                None
+            }
+        }));
+
+        if self.file_entries.iter().any(|data| data.is_started()) {
+            subscriptions.push(iced::time::every(Duration::from_secs(1)).map(|_| Message::Tick));
+        }
+
+        subscriptions.push(keyboard::on_key_press(|key, modifiers| {
+            match (key.as_ref(), modifiers.command()) {
+                (keyboard::Key::Character("p"), true) => Some(Message::TogglePauseAll),
+                (keyboard::Key::Character("."), true) => Some(Message::CancelAll),
+                (keyboard::Key::Named(keyboard::key::Named::ArrowUp), false) => {
+                    Some(Message::MoveSelection(-1))
+                }
+                (keyboard::Key::Named(keyboard::key::Named::ArrowDown), false) => {
+                    Some(Message::MoveSelection(1))
+                }
+                (keyboard::Key::Named(keyboard::key::Named::Space), false) => {
+                    Some(Message::TogglePauseSelected)
+                }
+                (
+                    keyboard::Key::Named(
+                        keyboard::key::Named::Delete | keyboard::key::Named::Backspace,
+                    ),
+                    false,
+                ) => Some(Message::CancelSelected),
+                _ => None,
+            }
+        }));
+
+        Subscription::batch(subscriptions)
+    }
+
+    fn selectable_text_style(theme: &Theme, _status: text_input::Status) -> text_input::Style {
+        let palette = theme.extended_palette();
+
+        // tweaks the default active style.
+        text_input::Style {
+            background: Background::Color(palette.background.base.color),
+            border: Border::default(),
+            icon: palette.background.weak.text,
+            placeholder: palette.background.strong.color,
+            value: palette.background.base.text,
+            selection: palette.primary.weak.color,
+        }
+    }
+
+    /// Colors `value` by whether it matches `expected`.
+    fn comparison_style(
+        theme: &Theme,
+        expected: Option<&HashValue>,
+        value: Option<&HashValue>,
+    ) -> text_input::Style {
+        let palette = theme.extended_palette();
+
+        let background = match (expected, value) {
+            (Some(hash), Some(other_hash)) if hash == other_hash => {
+                Background::Color(palette.success.base.color)
+            }
+            (Some(_), Some(_)) => Background::Color(palette.danger.base.color),
+            _ => Background::Color(palette.background.base.color),
+        };
+
+        // tweaks the default active style.
+        text_input::Style {
+            background,
+            border: Border::default(),
+            icon: palette.background.weak.text,
+            placeholder: palette.background.strong.color,
+            value: palette.background.base.text,
+            selection: palette.primary.weak.color,
+        }
+    }
+
+    fn view_algorithms(&self) -> Element<'_, Message> {
+        column(
+            [
+                (Category::Cryptographic, "Hash"),
+                (Category::Insecure, "Insecure"),
+                (Category::Checksum, "Checksum"),
+                (Category::Mac, "Keyed"),
+            ]
+            .into_iter()
+            .map(|(category, title)| {
+                row([
+                    text(title).size(12).width(64).into(),
+                    row(self
+                        .registry
+                        .iter()
+                        .filter(|data| data.category() == category)
+                        .map(|data| self.view_algorithm(data.as_ref())))
+                    .spacing(8)
+                    .wrap()
+                    .into(),
+                ])
+                .into()
+            }),
+        )
+        .spacing(2)
+        .into()
+    }
+
+    fn view_options(&self) -> Element<'_, Message> {
+        let mut children = vec![
+            checkbox("Re-hash files changed during hashing", self.rehash_modified)
+                .size(14)
+                .text_size(12)
+                .on_toggle(Message::RehashModifiedToggled)
+                .into(),
+            text("Concurrent files").size(12).into(),
+            pick_list(
+                Self::MAX_JOBS_OPTIONS,
+                Some(self.max_jobs),
+                Message::MaxJobsSelected,
+            )
+            .text_size(12)
+            .into(),
+            checkbox("Verify checksum files", self.verify_checksums)
+                .size(14)
+                .text_size(12)
+                .on_toggle(Message::VerifyChecksumsToggled)
+                .into(),
+            checkbox("One file per device", self.per_device)
+                .size(14)
+                .text_size(12)
+                .on_toggle(Message::PerDeviceToggled)
+                .into(),
+            text("Read").size(12).into(),
+            pick_list(
+                ReadBackend::ALL,
+                Some(self.read_backend),
+                Message::ReadBackendSelected,
+            )
+            .text_size(12)
+            .into(),
+        ];
+        if self.read_backend == ReadBackend::Mmap {
+            children.push(
+                text("A file truncated while mapped crashes the app")
+                    .size(12)
+                    .color(self.theme().extended_palette().danger.base.color)
+                    .into(),
+            );
+        }
+        row(children)
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .wrap()
+            .into()
+    }
+
+    /// Colors the results by whether they match `reference`, which is `None` for the first
+    /// standalone entry and the members of a directory or a checksum file.
+    fn view_file_entry<'a>(
+        &'a self,
+        data: &'a FileEntry,
+        reference: Option<&'a FileEntry>,
+    ) -> Vec<Element<'a, Message>> {
+        let mut children = vec![];
+
+        let selected = data.is_active() && self.selected.as_ref() == Some(&data.pathname);
+        let mut pathname_row = vec![
+            if selected {
+                text("▸ pathname: ")
+                    .color(self.theme().extended_palette().primary.strong.color)
+                    .into()
+            } else {
+                text("pathname: ").into()
+            },
+            text_input("", &data.pathname.display().to_string())
+                .size(12)
+                .style(Self::selectable_text_style)
+                .into(),
+        ];
+        if data.is_active() {
+            pathname_row.extend(self.view_job_controls(data));
+        }
+        children.push(
+            row(pathname_row)
+                .spacing(4)
+                .align_y(Alignment::Center)
+                .into(),
+        );
+        if let Some(target) = &data.target {
+            children.push(
+                row([
+                    text("target: ").into(),
+                    text_input("", &target.display().to_string())
+                        .size(12)
+                        .style(Self::selectable_text_style)
+                        .into(),
+                ])
+                .into(),
+            );
+        }
+        if let Some(e) = self.checksum_errors.get(&data.pathname) {
+            children.push(
+                text(format!("Not verified as a checksum file: {e}"))
+                    .size(12)
+                    .color(self.theme().extended_palette().danger.base.color)
+                    .into(),
+            );
+        }
+
+        match &data.state {
+            FileEntryState::Waiting => {
+                for algorithm in &data.algorithms {
+                    children.push(
+                        row([
+                            self.view_label(algorithm.as_ref()),
+                            text("Waiting").size(12).into(),
+                        ])
+                        .align_y(Alignment::Center)
+                        .into(),
+                    );
+                }
+            }
+            FileEntryState::Idle | FileEntryState::Calculating { .. } => {
+                if let FileEntryState::Calculating { progress } = data.state {
+                    children.push(
+                        text(data.throughput.describe(progress, Instant::now()))
+                            .size(12)
+                            .into(),
+                    );
+                }
+                let (processed, percent) = match data.state {
+                    FileEntryState::Calculating { progress } => {
+                        (progress.processed, progress.percent())
+                    }
+                    _ => (0, Some(0.0)),
+                };
+                for algorithm in &data.algorithms {
+                    children.push(
+                        row([
+                            self.view_label(algorithm.as_ref()),
+                            match percent {
+                                Some(percent) => {
+                                    progress_bar(0.0..=100.0, percent).height(16).into()
+                                }
+                                // the size of a stream is unknown until EOF.
+                                None => text(format!("{} read", format_bytes(processed)))
+                                    .size(12)
+                                    .into(),
+                            },
+                        ])
+                        .align_y(Alignment::Center)
+                        .into(),
+                    );
+                }
+            }
+            FileEntryState::Cancelled => {
+                let pathname = data.pathname.clone();
+                children.push(
+                    row([
+                        text("Cancelled").size(12).width(Length::Fill).into(),
+                        button(text("Restart").size(12))
+                            .padding([2, 8])
+                            .on_press(Message::Retry(pathname))
+                            .into(),
+                    ])
+                    .spacing(4)
+                    .align_y(Alignment::Center)
+                    .into(),
+                );
+            }
+            FileEntryState::Failed { kind, message } => {
+                let pathname = data.pathname.clone();
+                children.push(
+                    row([
+                        text(format!("{message} ({kind:?})"))
+                            .size(12)
+                            .color(self.theme().extended_palette().danger.base.color)
+                            .width(Length::Fill)
+                            .into(),
+                        button(text("Retry").size(12))
+                            .padding([2, 8])
+                            .on_press(Message::Retry(pathname))
+                            .into(),
+                    ])
+                    .spacing(4)
+                    .align_y(Alignment::Center)
+                    .into(),
+                );
+            }
+            FileEntryState::Finished { results, modified } => {
+                if let Some(summary) = data.throughput.summary() {
+                    children.push(text(summary).size(12).into());
+                }
+                if *modified {
+                    let pathname = data.pathname.clone();
+                    children.push(
+                        row([
+                            text("File changed during hashing")
+                                .size(12)
+                                .color(self.theme().extended_palette().danger.base.color)
+                                .width(Length::Fill)
+                                .into(),
+                            button(text("Re-hash").size(12))
+                                .padding([2, 8])
+                                .on_press(Message::Retry(pathname))
+                                .into(),
+                        ])
+                        .spacing(4)
+                        .align_y(Alignment::Center)
+                        .into(),
+                    );
+                }
+                for result in results {
+                    let algorithm = result.algorithm.as_ref();
+                    children.push(
+                        row([
+                            self.view_label(algorithm),
+                            text_input("", &result.value.to_string())
+                                .size(12)
+                                .style(move |theme, status| match reference {
+                                    Some(reference) => Self::comparison_style(
+                                        theme,
+                                        reference.find_hash(algorithm),
+                                        Some(&result.value),
+                                    ),
+                                    None => Self::selectable_text_style(theme, status),
+                                })
+                                .into(),
+                        ])
+                        .align_y(Alignment::Center)
+                        .into(),
+                    );
+                }
+            }
+        }
+
+        children
+    }
+
+    fn view_directory<'a>(
+        &'a self,
+        index: usize,
+        directory: &'a DirectoryEntry,
+    ) -> Vec<Element<'a, Message>> {
+        let root = directory.root.clone();
+        let mut children = vec![
+            row([
+                text("directory: ").into(),
+                text_input("", &directory.root.display().to_string())
+                    .size(12)
+                    .style(Self::selectable_text_style)
+                    .into(),
+                button(
+                    text(if directory.expanded {
+                        "Collapse"
+                    } else {
+                        "Expand"
+                    })
+                    .size(12),
+                )
+                .padding([2, 8])
+                .on_press(Message::DirectoryExpanded(root, !directory.expanded))
+                .into(),
+            ])
+            .spacing(4)
+            .align_y(Alignment::Center)
+            .into(),
+            text(format!("files: {}", directory.options))
+                .size(12)
+                .into(),
+        ];
+
+        // digests of differently listed files are not comparable.
+        let reference = self
+            .directories
+            .first()
+            .filter(|data| 0 < index && data.options == directory.options);
+        match &directory.results {
+            Some(results) => {
+                for (algorithm, result) in directory.algorithms.iter().zip(results) {
+                    let value = match result {
+                        Some(data) => data.value.to_string(),
+                        None => "hashed with other algorithms before".into(),
+                    };
+                    let expected = reference.map(|data| data.find_hash(algorithm.as_ref()));
+                    let actual = result.as_ref().map(|data| &data.value);
+                    children.push(
+                        row([
+                            self.view_label(algorithm.as_ref()),
+                            text_input("", &value)
+                                .size(12)
+                                .style(move |theme, status| match expected {
+                                    Some(expected) => {
+                                        Self::comparison_style(theme, expected, actual)
+                                    }
+                                    None => Self::selectable_text_style(theme, status),
+                                })
+                                .into(),
+                        ])
+                        .align_y(Alignment::Center)
+                        .into(),
+                    );
+                }
+            }
+            None => {
+                let (done, failed) = directory.files.iter().fold((0, 0), |(done, failed), file| {
+                    match self
+                        .file_entries
+                        .iter()
+                        .find(|data| data.pathname == file.pathname)
+                    {
+                        Some(FileEntry {
+                            state: FileEntryState::Finished { .. },
+                            ..
+                        }) => (done + 1, failed),
+                        Some(FileEntry {
+                            state: FileEntryState::Failed { .. } | FileEntryState::Cancelled,
+                            ..
+                        }) => (done, failed + 1),
+                        _ => (done, failed),
+                    }
+                });
+                let mut status = format!("{done}/{} files hashed", directory.files.len());
+                if 0 < failed {
+                    status.push_str(&format!(", {failed} failed or cancelled"));
+                }
+                children.push(text(status).size(12).into());
+            }
+        }
+
+        if directory.expanded {
+            children.push(self.view_members(|pathname| {
+                directory.files.iter().any(|file| file.pathname == pathname)
+            }));
+        }
+
+        children
+    }
+
+    fn view_checksum_file<'a>(
+        &'a self,
+        checksum_file: &'a ChecksumEntry,
+    ) -> Vec<Element<'a, Message>> {
+        let theme = self.theme();
+        let palette = theme.extended_palette();
+        let statuses = checksum_file
+            .lines
+            .iter()
+            .map(|line| self.checksum_status(line, checksum_file.algorithm.as_ref()))
+            .collect::<Vec<_>>();
+
+        // in the order of the `sha256sum -c` warnings.
+        let mut summary = [
+            ChecksumStatus::Ok,
+            ChecksumStatus::Failed,
+            ChecksumStatus::Missing,
+            ChecksumStatus::Unreadable,
+            ChecksumStatus::Cancelled,
+            ChecksumStatus::Pending,
+        ]
+        .into_iter()
+        .filter_map(|status| {
+            let count = statuses.iter().filter(|data| **data == status).count();
+            (0 < count).then(|| format!("{count} {status}"))
+        })
+        .collect::<Vec<_>>();
+        match checksum_file.improperly_formatted {
+            0 => {}
+            1 => summary.push("1 line improperly formatted".into()),
+            count => summary.push(format!("{count} lines improperly formatted")),
+        }
+        let summary = summary.join(", ");
+
+        let pathname = checksum_file.pathname.clone();
+        let mut children = vec![
+            row([
+                text("checksums: ").into(),
+                text_input("", &checksum_file.pathname.display().to_string())
+                    .size(12)
+                    .style(Self::selectable_text_style)
+                    .into(),
+                button(
+                    text(if checksum_file.expanded {
+                        "Collapse"
+                    } else {
+                        "Expand"
+                    })
+                    .size(12),
+                )
+                .padding([2, 8])
+                .on_press(Message::ChecksumExpanded(pathname, !checksum_file.expanded))
+                .into(),
+            ])
+            .spacing(4)
+            .align_y(Alignment::Center)
+            .into(),
+            row([
+                self.view_label(checksum_file.algorithm.as_ref()),
+                text(summary).size(12).into(),
+            ])
+            .align_y(Alignment::Center)
+            .into(),
+        ];
+
+        for (line, status) in checksum_file.lines.iter().zip(statuses) {
+            let color = match status {
+                ChecksumStatus::Ok => Some(palette.success.base.color),
+                ChecksumStatus::Failed | ChecksumStatus::Missing | ChecksumStatus::Unreadable => {
+                    Some(palette.danger.base.color)
+                }
+                ChecksumStatus::Cancelled | ChecksumStatus::Pending => None,
+            };
+            children.push(
+                row([
+                    text(status.to_string())
+                        .size(12)
+                        .color_maybe(color)
+                        .width(140)
+                        .into(),
+                    text(&line.name).size(12).into(),
+                ])
+                .into(),
+            );
+        }
+
+        if checksum_file.expanded {
+            children.push(self.view_members(|pathname| {
+                checksum_file
+                    .lines
+                    .iter()
+                    .any(|line| line.pathname == pathname)
+            }));
+        }
+
+        children
+    }
+
+    /// Indents the file entries that belong to a directory or a checksum file.
+    fn view_members<'a>(&'a self, contains: impl Fn(&Path) -> bool) -> Element<'a, Message> {
+        let members = column(
+            self.file_entries
+                .iter()
+                .filter(|data| contains(&data.pathname))
+                .flat_map(|data| self.view_file_entry(data, None)),
+        );
+        container(members).padding(padding::left(16)).into()
+    }
+
+    fn view_walk_options(&self) -> Element<'_, Message> {
+        let input = |field, placeholder, width| {
+            text_input(placeholder, self.walk_inputs.get(field))
+                .size(12)
+                .width(width)
+                .on_input(move |value| Message::WalkOptionChanged(field, value))
+                .into()
+        };
+
+        let mut children = vec![
+            text("Folders").size(12).width(64).into(),
+            checkbox("Follow symlinks", self.walk_inputs.follow_symlinks)
+                .size(14)
+                .text_size(12)
+                .on_toggle(Message::WalkFollowSymlinksToggled)
+                .into(),
+            checkbox("Hidden files", self.walk_inputs.include_hidden)
+                .size(14)
+                .text_size(12)
+                .on_toggle(Message::WalkHiddenToggled)
+                .into(),
+            checkbox("Single digest", self.directory_digest)
+                .size(14)
+                .text_size(12)
+                .on_toggle(Message::DirectoryDigestToggled)
+                .into(),
+            input(WalkField::MaxDepth, "max depth", 72),
+            input(WalkField::Include, "include globs", 128),
+            input(WalkField::Exclude, "exclude globs", 128),
+        ];
+        if let Some(e) = self
+            .walk_inputs
+            .parse()
+            .err()
+            .map(|e| format!("{e:#}"))
+            .or_else(|| self.walk_error.clone())
+        {
+            children.push(
+                text(e)
+                    .size(12)
+                    .color(self.theme().extended_palette().danger.base.color)
+                    .into(),
+            );
+        }
+
+        row(children)
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .wrap()
+            .into()
+    }
+
+    fn view_algorithm(&self, algorithm: &dyn Algorithm) -> Element<'_, Message> {
+        let name = algorithm.name().to_string();
+        let toggle = checkbox(
+            algorithm.name(),
+            self.algorithms
+                .iter()
+                .any(|data| data.name() == algorithm.name()),
+        )
+        .size(14)
+        .text_size(12)
+        .on_toggle(move |checked| Message::AlgorithmToggled(name.clone(), checked));
+
+        let limits = algorithm.param_limits();
+        if algorithm.output_size_range().is_none() && limits.is_empty() {
+            return toggle.into();
+        }
+
+        let mut children = vec![toggle.into()];
+        if let Some(range) = algorithm.output_size_range() {
+            children.push(self.view_param_input(
+                algorithm.name(),
+                ParamField::OutputSize,
+                format!("{}-{} bytes", range.start(), range.end()),
+            ));
+        }
+        if 0 < limits.key {
+            children.extend(self.view_key_input(algorithm.name(), limits.key));
+        }
+        if 0 < limits.salt {
+            children.push(self.view_param_input(
+                algorithm.name(),
+                ParamField::Salt,
+                placeholder("salt hex", limits.salt),
+            ));
+        }
+        if 0 < limits.personalization {
+            children.push(self.view_param_input(
+                algorithm.name(),
+                ParamField::Personalization,
+                placeholder("personal hex", limits.personalization),
+            ));
+        }
+        if let Err(e) = self.configured_algorithm(algorithm.name()) {
+            children.push(
+                text(format!("{e:#}"))
+                    .size(12)
+                    .color(self.theme().extended_palette().danger.base.color)
+                    .into(),
+            );
+        }
+
+        row(children).spacing(4).align_y(Alignment::Center).into()
+    }
+
+    fn view_param_input(
+        &self,
+        name: &str,
+        field: ParamField,
+        placeholder: String,
+    ) -> Element<'_, Message> {
+        let owned_name = name.to_string();
+        text_input(
+            &placeholder,
+            self.param_inputs
+                .get(name)
+                .map(|data| data.get(field))
+                .unwrap_or_default(),
+        )
+        .size(12)
+        .width(match field {
+            ParamField::OutputSize => 88,
+            ParamField::Salt | ParamField::Personalization => 128,
+        })
+        .on_input(move |value| Message::ParamChanged(owned_name.clone(), field, value))
+        .into()
+    }
+
+    fn view_key_input(&self, name: &str, limit: usize) -> [Element<'_, Message>; 2] {
+        let inputs = self.param_inputs.get(name);
+        let encoding = inputs.map(|data| data.key_encoding).unwrap_or_default();
+        let input_name = name.to_string();
+        let encoding_name = name.to_string();
+        [
+            text_input(
+                &placeholder(&format!("key {encoding}"), limit),
+                inputs.map(|data| data.key.0.as_str()).unwrap_or_default(),
+            )
+            .size(12)
+            .width(128)
+            .secure(true)
+            .on_input(move |value| Message::KeyChanged(input_name.clone(), Secret(value)))
+            .into(),
+            pick_list(KeyEncoding::ALL, Some(encoding), move |encoding| {
+                Message::KeyEncodingSelected(encoding_name.clone(), encoding)
+            })
+            .text_size(12)
+            .into(),
+        ]
+    }
+
+    fn view_label(&self, algorithm: &dyn Algorithm) -> Element<'_, Message> {
+        let label = text(format!("{}: ", algorithm.label()));
+        match algorithm.category() {
+            Category::Cryptographic | Category::Checksum | Category::Mac => label.into(),
+            Category::Insecure => row([
+                text("INSECURE")
+                    .size(10)
+                    .color(self.theme().extended_palette().danger.base.color)
+                    .into(),
+                Space::with_width(4).into(),
+                label.into(),
+            ])
+            .align_y(Alignment::Center)
+            .into(),
+        }
+    }
+
+    fn view_job_controls(&self, entry: &FileEntry) -> [Element<'_, Message>; 3] {
+        let paused = entry.control.state() == JobState::Paused && !entry.resume_requested;
+        [
+            text(if entry.resume_requested {
+                "Resuming when a slot is free"
+            } else if paused {
+                "Paused"
+            } else {
+                ""
+            })
+            .size(12)
+            .into(),
+            button(text(if paused { "Resume" } else { "Pause" }).size(12))
+                .padding([2, 8])
+                .on_press(if paused {
+                    Message::Resume(entry.pathname.clone())
+                } else {
+                    Message::Pause(entry.pathname.clone())
+                })
+                .into(),
+            button(text("Cancel").size(12))
+                .padding([2, 8])
+                .style(button::danger)
+                .on_press(Message::Cancel(entry.pathname.clone()))
+                .into(),
+        ]
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        if self.is_empty() {
+            return column([
+                self.view_algorithms(),
+                self.view_options(),
+                self.view_walk_options(),
+                container(column([
+                    row([
+                        text("Calculate").into(),
+                        Space::with_width(4).into(),
+                        text("Drop files here")
+                            .color(self.theme().extended_palette().primary.strong.color)
+                            .into(),
+                    ])
+                    .into(),
+                    row([
+                        text("Verify").into(),
+                        Space::with_width(4).into(),
+                        text("Drop SHA256SUMS or other checksum files")
+                            .color(self.theme().extended_palette().primary.strong.color)
+                            .into(),
+                    ])
+                    .into(),
+                    row([
+                        text("Clear/Exit").into(),
+                        Space::with_width(4).into(),
+                        text(if cfg!(target_os = "macos") {
+                            "⎋"
+                        } else {
+                            "Esc"
+                        })
+                        .color(self.theme().extended_palette().primary.strong.color)
+                        .into(),
+                    ])
+                    .into(),
+                    row([
+                        text("Pause/Resume all").into(),
+                        Space::with_width(4).into(),
+                        text(if cfg!(target_os = "macos") {
+                            "⌘P"
+                        } else {
+                            "Ctrl+P"
+                        })
+                        .color(self.theme().extended_palette().primary.strong.color)
+                        .into(),
+                    ])
+                    .into(),
+                    row([
+                        text("Cancel all").into(),
+                        Space::with_width(4).into(),
+                        text(if cfg!(target_os = "macos") {
+                            "⌘."
+                        } else {
+                            "Ctrl+."
+                        })
+                        .color(self.theme().extended_palette().primary.strong.color)
+                        .into(),
+                    ])
+                    .into(),
+                    row([
+                        text("Select a running file").into(),
+                        Space::with_width(4).into(),
+                        text("↑/↓")
+                            .color(self.theme().extended_palette().primary.strong.color)
+                            .into(),
+                    ])
+                    .into(),
+                    row([
+                        text("Pause/Resume or cancel it").into(),
+                        Space::with_width(4).into(),
+                        text(if cfg!(target_os = "macos") {
+                            "Space / ⌫"
+                        } else {
+                            "Space / Delete"
+                        })
+                        .color(self.theme().extended_palette().primary.strong.color)
+                        .into(),
+                    ])
+                    .into(),
+                ]))
+                .center(Length::Fill)
+                .into(),
+            ])
+            .into();
+        }
+
+        let members = self
+            .directories
+            .iter()
+            .flat_map(|data| data.files.iter().map(|file| file.pathname.as_path()))
+            .chain(
+                self.checksum_files
+                    .iter()
+                    .flat_map(|data| data.lines.iter().map(|line| line.pathname.as_path())),
+            )
+            .collect::<HashSet<_>>();
+        let mut sections = self
+            .directories
+            .iter()
+            .enumerate()
+            .map(|(i, data)| self.view_directory(i, data))
+            .collect::<Vec<_>>();
+        sections.extend(
+            self.checksum_files
+                .iter()
+                .map(|data| self.view_checksum_file(data)),
+        );
+        let mut standalone = self
+            .file_entries
+            .iter()
+            .filter(|data| !members.contains(data.pathname.as_path()));
+        if let Some(first) = standalone.next() {
+            sections.push(self.view_file_entry(first, None));
+            sections.extend(standalone.map(|data| self.view_file_entry(data, Some(first))));
+        }
+
+        let mut children = vec![];
+        for (i, section) in sections.into_iter().enumerate() {
+            if 0 < i {
+                children.push(horizontal_rule(8).into());
+            }
+            children.extend(section);
+        }
+
+        column([
+            self.view_algorithms(),
+            self.view_options(),
+            self.view_walk_options(),
+            horizontal_rule(8).into(),
+            scrollable(column(children)).height(Length::Fill).into(),
+            horizontal_rule(8).into(),
+            self.view_status(),
+        ])
+        .into()
+    }
+
+    fn view_status(&self) -> Element<'_, Message> {
+        let now = Instant::now();
+        let count = |filter: fn(&FileEntryState) -> bool| {
+            self.file_entries
+                .iter()
+                .filter(|data| filter(&data.state))
+                .count()
+        };
+        let running = count(|state| {
+            matches!(
+                state,
+                FileEntryState::Idle | FileEntryState::Calculating { .. }
+            )
+        });
+        let waiting = count(|state| matches!(state, FileEntryState::Waiting));
+
+        let mut status = format!("{running} running, {waiting} waiting");
+        if 0 < running {
+            let rate = self
+                .file_entries
+                .iter()
+                .filter(|data| data.is_started())
+                .map(|data| data.throughput.rate(now))
+                .sum::<f64>();
+            let remaining = self
+                .file_entries
+                .iter()
+                .filter_map(|data| match data.state {
+                    FileEntryState::Calculating { progress } => progress
+                        .total
+                        .map(|total| total.saturating_sub(progress.processed)),
+                    _ => None,
+                })
+                .sum::<u64>();
+            status.push_str(&format!(" · {}/s", format_bytes(rate as u64)));
+            if 0.0 < rate {
+                status.push_str(&format!(
+                    " · ETA {}",
+                    format_duration(Duration::from_secs_f64(remaining as f64 / rate)),
+                ));
+            }
+        }
+
+        text(status).size(12).into()
+    }
+
+    fn theme(&self) -> Theme {
+        Theme::default()
+    }
+
+    fn hash(entry: FileEntry) -> impl Stream<Item = FileEntry> {
+        iced::stream::channel(3, async move |mut output| {
+            let mut progress_output = output.clone();
+            let progress_entry = entry.clone();
+            let ret = tokio::task::spawn_blocking(move || {
+                let mut last_progress = None;
+                // progress may be dropped while the buffer is full since a newer one follows.
+                let on_progress = |progress: Progress| {
+                    last_progress = Some(progress);
+
+                    // blocks while paused, which also stops the reader once the queue is full.
+                    if progress_entry.control.wait().is_break() {
+                        info!("cancelled");
+                        return ControlFlow::Break(());
+                    }
+
+                    match progress_output.try_send(FileEntry {
+                        state: FileEntryState::Calculating { progress },
+                        ..progress_entry.clone()
+                    }) {
+                        Err(e) if e.is_disconnected() => {
+                            info!("disconnected");
+                            ControlFlow::Break(())
+                        }
+                        Ok(_) | Err(_) => ControlFlow::Continue(()),
+                    }
+                };
+
+                let ret = engine::hash_file(
+                    &progress_entry.pathname,
+                    &progress_entry.algorithms,
+                    progress_entry.backend,
+                    on_progress,
+                );
+                (ret, last_progress)
+            })
+            .await;
+            let last_progress = ret.as_ref().ok().and_then(|data| data.1);
+
+            let state = match ret {
+                Ok((Ok(HashOutcome::Finished(results)), _)) => FileEntryState::Finished {
+                    results,
+                    modified: false,
+                },
+                Ok((Ok(HashOutcome::Modified(results)), _)) => FileEntryState::Finished {
+                    results,
+                    modified: true,
+                },
+                Ok((Ok(HashOutcome::Cancelled), _)) => return,
+                Ok((Err(e), _)) => {
+                    warn!(?e);
+                    let kind = e
+                        .chain()
+                        .find_map(|data| data.downcast_ref::<std::io::Error>())
+                        .map(std::io::Error::kind)
+                        .unwrap_or(std::io::ErrorKind::Other);
+                    FileEntryState::Failed {
+                        kind,
+                        message: format!("{e:#}"),
+                    }
+                }
+                Err(e) => {
+                    warn!(?e, "join");
+                    FileEntryState::Failed {
+                        kind: std::io::ErrorKind::Other,
+                        message: e.to_string(),
+                    }
+                }
+            };
+
+            // the last progress may have been dropped but the total bytes are needed for the
+            // throughput of the finished entry.
+            if let (FileEntryState::Finished { .. }, Some(progress)) = (&state, last_progress)
+                && let Err(e) = output
+                    .send(FileEntry {
+                        state: FileEntryState::Calculating { progress },
+                        ..entry.clone()
+                    })
+                    .await
+            {
+                info!(?e, "disconnected");
+            }
+
+            // waits for room in the buffer so that the completion is never dropped. every sender
+            // has its own slot, so the progress sender cannot starve this one.
+            if let Err(e) = output.send(FileEntry { state, ..entry }).await {
+                info!(?e, "disconnected");
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FileEntry {
+    pathname: PathBuf,
+    /// Resolved target if `pathname` is a symlink.
+    target: Option<PathBuf>,
+    algorithms: Vec<Arc<dyn Algorithm>>,
+    backend: ReadBackend,
+    state: FileEntryState,
+    /// Shared with the running job. Replaced on restart.
+    control: Arc<JobControl>,
+    throughput: Throughput,
+    /// Automatic re-hashes after the file changed during hashing.
+    rehashes: usize,
+    /// Bumped on every restart to give the job a new subscription id.
+    generation: usize,
+    /// Paused job waiting for a slot to resume in.
+    resume_requested: bool,
+    /// Device of the file for [`App::per_device`].
+    device: Option<u64>,
+    /// Size when dropped for the overall progress. `None` for streams.
+    size: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+enum FileEntryState {
+    /// Queued until a job slot is free.
+    Waiting,
+    /// Scheduled and about to report the first progress.
     Idle,
-    Calculating { progress: f32 },
-    Finished { hash: String },
+    Calculating {
+        progress: Progress,
+    },
+    Finished {
+        results: Vec<HashResult>,
+        /// The file changed during hashing.
+        modified: bool,
+    },
+    Failed {
+        kind: std::io::ErrorKind,
+        message: String,
+    },
+    Cancelled,
+}
+
+#[derive(Debug, Default)]
+struct OverallProgress {
+    processed: u64,
+    total: u64,
+    done: usize,
+    files: usize,
+}
+
+impl OverallProgress {
+    fn percent(&self) -> f32 {
+        match (self.total, self.files) {
+            (0, 0) => 0.0,
+            (0, files) => (self.done as f64 / files as f64 * 100.0) as f32,
+            (total, _) => (self.processed as f64 / total as f64 * 100.0) as f32,
+        }
+    }
+}
+
+/// Transfer rates measured from the progress received by the UI.
+#[derive(Debug, Clone, Copy, Default)]
+struct Throughput {
+    started: Option<Instant>,
+    finished: Option<Instant>,
+    processed: u64,
+    /// The last progress used for the rate and when it arrived.
+    last: Option<(Instant, u64)>,
+    /// Smoothed bytes per second.
+    rate: f64,
+}
+
+impl Throughput {
+    /// Shorter intervals make the rate jitter with the chunk size.
+    const RATE_INTERVAL: Duration = Duration::from_millis(500);
+    /// No progress for this long is reported as 0 B/s, i.e. stalled.
+    const STALL_TIMEOUT: Duration = Duration::from_secs(3);
+
+    fn update(&mut self, now: Instant, processed: u64) {
+        self.started.get_or_insert(now);
+        self.processed = processed;
+        match self.last {
+            Some((last, last_processed)) => {
+                let interval = now - last;
+                if interval < Self::RATE_INTERVAL {
+                    return;
+                }
+                let rate = processed.saturating_sub(last_processed) as f64 / interval.as_secs_f64();
+                self.rate = match self.rate {
+                    0.0 => rate,
+                    _ => self.rate * 0.7 + rate * 0.3,
+                };
+                self.last = Some((now, processed));
+            }
+            None => self.last = Some((now, processed)),
+        }
+    }
+
+    fn elapsed(&self, now: Instant) -> Duration {
+        match self.started {
+            Some(started) => self.finished.unwrap_or(now) - started,
+            None => Duration::ZERO,
+        }
+    }
+
+    /// Current bytes per second.
+    fn rate(&self, now: Instant) -> f64 {
+        match self.last {
+            Some((last, _)) if now - last < Self::STALL_TIMEOUT => self.rate,
+            _ => 0.0,
+        }
+    }
+
+    fn average(&self, processed: u64, now: Instant) -> f64 {
+        match self.elapsed(now).as_secs_f64() {
+            0.0 => 0.0,
+            elapsed => processed as f64 / elapsed,
+        }
+    }
+
+    fn describe(&self, progress: Progress, now: Instant) -> String {
+        let mut ret = match progress.total {
+            Some(total) => format!(
+                "{} / {}",
+                format_bytes(progress.processed),
+                format_bytes(total),
+            ),
+            None => format_bytes(progress.processed),
+        };
+        let rate = self.rate(now);
+        ret.push_str(&format!(
+            " · {}/s (avg {}/s) · {} elapsed",
+            format_bytes(rate as u64),
+            format_bytes(self.average(progress.processed, now) as u64),
+            format_duration(self.elapsed(now)),
+        ));
+        if let Some(total) = progress.total
+            && 0.0 < rate
+        {
+            let remaining = total.saturating_sub(progress.processed) as f64 / rate;
+            ret.push_str(&format!(
+                " · ETA {}",
+                format_duration(Duration::from_secs_f64(remaining)),
+            ));
+        }
+        ret
+    }
+
+    /// Describes a finished job.
+    fn summary(&self) -> Option<String> {
+        let finished = self.finished?;
+        self.started?;
+        Some(format!(
+            "{} in {} (avg {}/s)",
+            format_bytes(self.processed),
+            format_duration(self.elapsed(finished)),
+            format_bytes(self.average(self.processed, finished) as u64),
+        ))
+    }
+}
+
+#[derive(Debug)]
+struct DirectoryEntry {
+    root: PathBuf,
+    /// Listed the files with, which the digest depends on.
+    options: WalkOptions,
+    /// In the manifest order.
+    files: Vec<DirectoryFile>,
+    algorithms: Vec<Arc<dyn Algorithm>>,
+    /// Per algorithm once every file is finished. `None` for an algorithm that some file was not
+    /// hashed with.
+    results: Option<Vec<Option<HashResult>>>,
+    /// Shows the file entries.
+    expanded: bool,
+}
+
+#[derive(Debug)]
+struct ChecksumEntry {
+    pathname: PathBuf,
+    algorithm: Arc<dyn Algorithm>,
+    lines: Vec<ChecksumLine>,
+    /// Lines skipped when parsing.
+    improperly_formatted: usize,
+    /// Shows the file entries.
+    expanded: bool,
+}
+
+/// Verification result of a [`ChecksumLine`], worded like `sha256sum -c`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ChecksumStatus {
+    Pending,
+    Ok,
+    Failed,
+    Missing,
+    /// Failed for another reason than a missing file.
+    Unreadable,
+    Cancelled,
+}
+
+impl Display for ChecksumStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            ChecksumStatus::Pending => "pending",
+            ChecksumStatus::Ok => "OK",
+            ChecksumStatus::Failed => "FAILED",
+            ChecksumStatus::Missing => "MISSING",
+            ChecksumStatus::Unreadable => "FAILED open or read",
+            ChecksumStatus::Cancelled => "cancelled",
+        })
+    }
+}
+
+impl DirectoryEntry {
+    fn find_hash(&self, algorithm: &dyn Algorithm) -> Option<&HashValue> {
+        self.results
+            .iter()
+            .flatten()
+            .flatten()
+            .find(|data| algorithm::is_comparable(data.algorithm.as_ref(), algorithm))
+            .map(|data| &data.value)
+    }
+}
+
+impl FileEntry {
+    fn find_hash(&self, algorithm: &dyn Algorithm) -> Option<&HashValue> {
+        match &self.state {
+            FileEntryState::Finished { results, .. } => results
+                .iter()
+                .find(|data| algorithm::is_comparable(data.algorithm.as_ref(), algorithm))
+                .map(|data| &data.value),
+            _ => None,
+        }
+    }
+
+    /// Queues the entry again as a new job.
+    fn restart(&mut self) {
+        self.state = FileEntryState::Waiting;
+        self.control = Default::default();
+        self.throughput = Throughput::default();
+        self.generation += 1;
+        self.resume_requested = false;
+    }
+
+    /// Pauses the job, or keeps it paused if a resume is pending.
+    fn pause(&mut self) {
+        self.control.pause();
+        self.resume_requested = false;
+    }
+
+    /// Resumes the job once [`App::schedule`] finds a free slot, since a paused job does not
+    /// count against [`App::max_jobs`].
+    fn resume(&mut self) {
+        if self.is_started() {
+            self.resume_requested = true;
+        } else {
+            self.control.resume();
+        }
+    }
+
+    /// Not paused, or about to be resumed.
+    fn is_running(&self) -> bool {
+        self.control.state() == JobState::Running || self.resume_requested
+    }
+
+    /// Waiting for or being hashed, including paused.
+    fn is_active(&self) -> bool {
+        match self.state {
+            FileEntryState::Waiting => true,
+            _ => self.is_started(),
+        }
+    }
+
+    /// Has a running job.
+    fn is_started(&self) -> bool {
+        match self.state {
+            FileEntryState::Idle | FileEntryState::Calculating { .. } => true,
+            FileEntryState::Waiting
+            | FileEntryState::Finished { .. }
+            | FileEntryState::Failed { .. }
+            | FileEntryState::Cancelled => false,
+        }
+    }
+}
+
+/// `limit` is in bytes and `usize::MAX` means any length.
+fn placeholder(title: &str, limit: usize) -> String {
+    match limit {
+        usize::MAX => title.into(),
+        _ => format!("{title} ≤{limit}"),
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while 1024.0 <= value && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{bytes} B"),
+        _ => format!("{value:.1} {}", UNITS[unit]),
+    }
+}
+
+#[cfg(unix)]
+fn device(metadata: &std::fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some(metadata.dev())
+}
+
+#[cfg(not(unix))]
+fn device(_metadata: &std::fs::Metadata) -> Option<u64> {
+    None
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs / 3600 {
+        0 => format!("{}:{:02}", secs / 60, secs % 60),
+        hours => format!("{hours}:{:02}:{:02}", secs / 60 % 60, secs % 60),
+    }
 }
@@ -0,0 +1,184 @@
+use hash_gui::prelude::*;
+use iced::window::raw_window_handle::{RawWindowHandle, WindowHandle};
+
+/// Publishes the overall progress to the taskbar or dock.
+///
+/// Implemented with `ITaskbarList3` on Windows once [`Taskbar::attach`] is called, and with the
+/// Unity launcher API on Linux, which is supported by KDE Plasma and the Dash to Dock extension of
+/// GNOME among others. Launchers only show the progress once `assets/hash-gui.desktop` is
+/// installed, e.g. to `~/.local/share/applications`, with `hash-gui` on the `PATH`. The dock
+/// progress of macOS is not implemented.
+#[derive(Default)]
+pub struct Taskbar {
+    /// Skips redundant updates since the progress is published on every message.
+    published: Option<Option<u32>>,
+    #[cfg(target_os = "linux")]
+    launcher: launcher::Launcher,
+    #[cfg(target_os = "windows")]
+    taskbar_list: Option<taskbar_list::TaskbarList3>,
+}
+
+/// Returns the `HWND` of a window, which the Windows taskbar needs.
+pub fn hwnd(handle: WindowHandle<'_>) -> Option<isize> {
+    match handle.as_raw() {
+        RawWindowHandle::Win32(data) => Some(data.hwnd.get()),
+        _ => None,
+    }
+}
+
+impl Taskbar {
+    /// Shows the progress on the taskbar button of the window `hwnd` from [`hwnd`].
+    pub fn attach(&mut self, hwnd: isize) {
+        #[cfg(target_os = "windows")]
+        match taskbar_list::TaskbarList3::new(hwnd) {
+            Ok(data) => {
+                if let Some(percent) = self.published {
+                    data.update(percent);
+                }
+                self.taskbar_list = Some(data);
+            }
+            Err(e) => warn!(?e, "ITaskbarList3"),
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        let _ = hwnd;
+    }
+
+    /// `percent` is `None` to hide the progress.
+    pub fn set_progress(&mut self, percent: Option<f32>) {
+        let percent = percent.map(|data| data.clamp(0.0, 100.0) as u32);
+        if self.published == Some(percent) {
+            return;
+        }
+        self.published = Some(percent);
+
+        #[cfg(target_os = "linux")]
+        self.launcher.update(percent);
+
+        #[cfg(target_os = "windows")]
+        if let Some(data) = &self.taskbar_list {
+            data.update(percent);
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod taskbar_list {
+    use super::*;
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::System::Com::{
+        CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED, CoCreateInstance, CoInitializeEx,
+    };
+    use windows::Win32::UI::Shell::{ITaskbarList3, TBPF_NOPROGRESS, TaskbarList};
+
+    /// Lives on the UI thread, which owns the window. The calls return quickly since the taskbar
+    /// only queues the update.
+    pub(super) struct TaskbarList3 {
+        list: ITaskbarList3,
+        hwnd: HWND,
+    }
+
+    impl TaskbarList3 {
+        pub(super) fn new(hwnd: isize) -> Fallible<Self> {
+            unsafe {
+                // winit has already initialized the thread for drag and drop, which succeeds with
+                // `S_FALSE` or fails with `RPC_E_CHANGED_MODE` and keeps the apartment either way.
+                if let Err(e) = CoInitializeEx(None, COINIT_APARTMENTTHREADED) {
+                    debug!(?e, "CoInitializeEx");
+                }
+                let list: ITaskbarList3 =
+                    CoCreateInstance(&TaskbarList, None, CLSCTX_INPROC_SERVER)?;
+                list.HrInit()?;
+                Ok(Self {
+                    list,
+                    hwnd: HWND(hwnd),
+                })
+            }
+        }
+
+        pub(super) fn update(&self, percent: Option<u32>) {
+            // the progress value shows the normal state implicitly.
+            let ret = unsafe {
+                match percent {
+                    Some(data) => self.list.SetProgressValue(self.hwnd, data.into(), 100),
+                    None => self.list.SetProgressState(self.hwnd, TBPF_NOPROGRESS),
+                }
+            };
+            if let Err(e) = ret {
+                warn!(?e, "ITaskbarList3");
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod launcher {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::mpsc::{Receiver, Sender};
+    use zbus::blocking::Connection;
+    use zbus::zvariant::Value;
+
+    /// Desktop entry installed from `assets/hash-gui.desktop`, which launchers match against.
+    const APP_URI: &str = "application://hash-gui.desktop";
+    const PATH: &str = "/com/canonical/unity/launcherentry/hash_gui";
+
+    /// Publishes from a dedicated thread since connecting to and writing to the session bus
+    /// block, which would stall the UI thread.
+    #[derive(Default)]
+    pub(super) struct Launcher {
+        /// Spawns the thread on the first update.
+        tx: Option<Sender<Option<u32>>>,
+    }
+
+    impl Launcher {
+        pub(super) fn update(&mut self, percent: Option<u32>) {
+            let tx = self.tx.get_or_insert_with(|| {
+                let (tx, rx) = std::sync::mpsc::channel();
+                if let Err(e) = std::thread::Builder::new()
+                    .name("launcher".into())
+                    .spawn(move || run(rx))
+                {
+                    warn!(?e, "spawn launcher");
+                }
+                tx
+            });
+            // fails once the thread is gone because the session bus is unavailable.
+            tx.send(percent).ok();
+        }
+    }
+
+    fn run(rx: Receiver<Option<u32>>) {
+        let connection = match Connection::session() {
+            Ok(data) => data,
+            Err(e) => {
+                info!(?e, "session bus is unavailable");
+                return;
+            }
+        };
+
+        while let Ok(mut percent) = rx.recv() {
+            // skips to the latest progress if the bus fell behind.
+            while let Ok(data) = rx.try_recv() {
+                percent = data;
+            }
+
+            let properties = HashMap::from([
+                (
+                    "progress",
+                    Value::from(f64::from(percent.unwrap_or_default()) / 100.0),
+                ),
+                ("progress-visible", Value::from(percent.is_some())),
+            ]);
+            if let Err(e) = connection.emit_signal(
+                None::<()>,
+                PATH,
+                "com.canonical.Unity.LauncherEntry",
+                "Update",
+                &(APP_URI, properties),
+            ) {
+                warn!(?e, "LauncherEntry.Update");
+            }
+        }
+    }
+}
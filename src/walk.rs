@@ -0,0 +1,109 @@
+use crate::prelude::*;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct WalkOptions {
+    /// Descends into symlinked directories and lists symlinked files. Symlinks are skipped
+    /// otherwise.
+    pub follow_symlinks: bool,
+    /// Lists dot files and descends into dot directories.
+    pub include_hidden: bool,
+    /// `Some(1)` lists the direct children only.
+    pub max_depth: Option<usize>,
+    /// Lists everything if empty.
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    /// Fails on an unreadable directory or a symlink loop instead of skipping it, for listings
+    /// that must be complete.
+    pub strict: bool,
+}
+
+/// Describes which files are listed, e.g. `dot files skipped, symlinks skipped, max depth 2`.
+impl Display for WalkOptions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut parts = vec![
+            if self.include_hidden {
+                "dot files included".to_owned()
+            } else {
+                "dot files skipped".to_owned()
+            },
+            if self.follow_symlinks {
+                "symlinks followed".to_owned()
+            } else {
+                "symlinks skipped".to_owned()
+            },
+        ];
+        if let Some(max_depth) = self.max_depth {
+            parts.push(format!("max depth {max_depth}"));
+        }
+        if !self.include.is_empty() {
+            parts.push(format!("include {}", self.include.join(" ")));
+        }
+        if !self.exclude.is_empty() {
+            parts.push(format!("exclude {}", self.exclude.join(" ")));
+        }
+        f.pad(&parts.join(", "))
+    }
+}
+
+/// Lists the regular files under `root` sorted by path. Globs are matched against the path
+/// relative to `root` with `/` separators, and `*` also matches `/`.
+pub fn walk(root: &Path, options: &WalkOptions) -> Fallible<Vec<PathBuf>> {
+    let include = glob_set(&options.include)?;
+    let exclude = glob_set(&options.exclude)?;
+
+    let mut walker = walkdir::WalkDir::new(root)
+        .follow_links(options.follow_symlinks)
+        .sort_by_file_name();
+    if let Some(max_depth) = options.max_depth {
+        walker = walker.max_depth(max_depth);
+    }
+
+    let mut ret = vec![];
+    let entries = walker.into_iter().filter_entry(|entry| {
+        options.include_hidden
+            || entry.depth() == 0
+            || !entry.file_name().to_string_lossy().starts_with('.')
+    });
+    for entry in entries {
+        let entry = match entry {
+            Ok(data) => data,
+            Err(e) if options.strict => {
+                return Err(e).with_context(|| format!("walk {}", root.display()));
+            }
+            Err(e) => {
+                // keeps going with the rest of the tree like `find` does.
+                warn!(?e, "walk");
+                continue;
+            }
+        };
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let relative = entry
+            .path()
+            .strip_prefix(root)
+            .unwrap_or(entry.path())
+            .to_string_lossy()
+            .replace(std::path::MAIN_SEPARATOR, "/");
+        if (options.include.is_empty() || include.is_match(&relative))
+            && !exclude.is_match(&relative)
+        {
+            ret.push(entry.into_path());
+        }
+    }
+
+    Ok(ret)
+}
+
+/// Fails on an invalid pattern.
+pub fn glob_set(patterns: &[String]) -> Fallible<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern).with_context(|| format!("glob {pattern}"))?);
+    }
+    Ok(builder.build()?)
+}